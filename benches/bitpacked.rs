@@ -0,0 +1,35 @@
+//! Time per generation of `GameOfLife` and `BitPacked` on a 4096x4096 torus filled with
+//! the same soup. Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use game_of_life::{bitpacked::BitPacked, soup::Soup, topology::WrapMode, Cell, GameOfLife};
+
+const SIZE: usize = 4096;
+const GENERATIONS: u32 = 10;
+
+fn time(mut update: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    (0..GENERATIONS).for_each(|_| update());
+    start.elapsed() / GENERATIONS
+}
+
+fn main() {
+    let mut game = GameOfLife::from_soup(SIZE, SIZE, WrapMode::Wrap, &Soup::new(1));
+    let mut packed = BitPacked::new(SIZE, SIZE, WrapMode::Wrap).unwrap();
+    for (x, y) in game.live_cells() {
+        packed.set(x, y, Cell::alive());
+    }
+
+    let bytes = time(|| game.update());
+    let bits = time(|| packed.update());
+    println!(
+        "{}x{} torus, per generation: GameOfLife {:?}, BitPacked {:?} ({:.1}x faster)",
+        SIZE,
+        SIZE,
+        bytes,
+        bits,
+        bytes.as_secs_f64() / bits.as_secs_f64()
+    );
+    assert_eq!(packed.population(), game.live_cells().count());
+}
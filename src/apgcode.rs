@@ -0,0 +1,353 @@
+//! Apgcodes, Catagolue's canonical names for still lifes, oscillators and spaceships.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    classify::{self, Classification},
+    pattern::Pattern,
+    rule::Rule,
+    Cell, GameOfLife,
+};
+
+// Column values, run lengths after `y` and strip separators share this alphabet.
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const STRIP_HEIGHT: usize = 5;
+
+/// Error reading an apgcode.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApgcodeError {
+    /// The code doesn't start with `xs`, `xp` or `xq` and a number.
+    InvalidPrefix(String),
+    /// Character that isn't part of the encoding, at a position counting from 1 within the
+    /// encoded cells.
+    UnexpectedChar(usize, char),
+    /// The number of live cells differs from the population in the prefix.
+    WrongPopulation(usize, usize),
+}
+
+impl Display for ApgcodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApgcodeError::InvalidPrefix(s) => {
+                write!(f, "invalid prefix '{}', expected xs, xp or xq", s)
+            }
+            ApgcodeError::UnexpectedChar(position, c) => {
+                write!(f, "character {}: unexpected '{}'", position, c)
+            }
+            ApgcodeError::WrongPopulation(expected, found) => write!(
+                f,
+                "prefix promises {} cells but the code has {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ApgcodeError {}
+
+/// What an object does over time, as given by the prefix of its apgcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Kind {
+    /// Unchanged from one generation to the next.
+    StillLife,
+    /// Period of the oscillation.
+    Oscillator(u64),
+    /// Period after which it reappears displaced.
+    Spaceship(u64),
+}
+
+/// Catagolue's name for an object, e.g. `xs4_33` for the block, `xp2_7` for the blinker and
+/// `xq4_153` for the glider. Among all phases and the eight orientations, the cells are
+/// those with the shortest and then alphabetically first code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Apgcode {
+    /// What the object does, which is also its prefix.
+    pub kind: Kind,
+    /// Live cells relative to the top left corner of their bounding box, row by row.
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Apgcode {
+    /// Code of an object given all of its phases. Only live cells count, so dying cells of
+    /// multistate rules are ignored.
+    pub fn from_phases(kind: Kind, phases: &[Vec<(i64, i64)>]) -> Self {
+        let code = |cells: &Vec<(usize, usize)>| {
+            let code = encode(cells);
+            (code.len(), code)
+        };
+        let cells = phases
+            .iter()
+            .flat_map(|phase| {
+                ORIENTATIONS.map(|[[a, b], [c, d]]| {
+                    normalized(phase.iter().map(|&(x, y)| (a * x + b * y, c * x + d * y)))
+                })
+            })
+            .map(|cells| (code(&cells), cells))
+            .min()
+            .map(|(_, cells)| cells)
+            .unwrap_or_default();
+        Self { kind, cells }
+    }
+
+    /// Runs the cells on the unbounded plane under the rule until they repeat, at most
+    /// `max_period` generations. Returns `None` for empty or unstable patterns, and for
+    /// rules with B0.
+    pub fn from_cells(
+        rule: Rule,
+        cells: impl IntoIterator<Item = (i64, i64)>,
+        max_period: u64,
+    ) -> Option<Self> {
+        let cells = cells.into_iter().map(|p| (p, Cell::alive()));
+        let (classification, phases) = classify::run(rule, cells, max_period).ok()?;
+        let kind = match classification {
+            Classification::StillLife => Kind::StillLife,
+            Classification::Oscillator { period } => Kind::Oscillator(period),
+            Classification::Spaceship(speed) => Kind::Spaceship(speed.period),
+            Classification::Extinct { .. } | Classification::Unstable => return None,
+        };
+        Some(Self::from_phases(kind, &phases))
+    }
+
+    /// Code of everything alive on the field taken as a single object, stepped on the
+    /// unbounded plane so that it can't interact with itself across edges.
+    pub fn from_game(game: &GameOfLife, max_period: u64) -> Option<Self> {
+        let cells = game.live_cells().map(|(x, y)| (x as i64, y as i64));
+        Self::from_cells(*game.rule(), cells, max_period)
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Pattern of the cells in the phase and orientation of the code.
+    pub fn to_pattern(&self) -> Pattern {
+        Pattern {
+            width: self.cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0),
+            height: self.cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0),
+            cells: self.cells.iter().map(|&p| (p, Cell::alive())).collect(),
+            ..Pattern::default()
+        }
+    }
+}
+
+impl Display for Apgcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            Kind::StillLife => write!(f, "xs{}", self.population())?,
+            Kind::Oscillator(period) => write!(f, "xp{}", period)?,
+            Kind::Spaceship(period) => write!(f, "xq{}", period)?,
+        }
+        write!(f, "_{}", encode(&self.cells))
+    }
+}
+
+impl FromStr for Apgcode {
+    type Err = ApgcodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ApgcodeError::InvalidPrefix(s.to_string());
+        let (prefix, code) = s.trim().split_once('_').ok_or_else(invalid)?;
+        let number = prefix.get(2..).ok_or_else(invalid)?;
+        let number: u64 = number.parse().map_err(|_| invalid())?;
+        let kind = match prefix.get(..2) {
+            Some("xs") => Kind::StillLife,
+            Some("xp") if number > 0 => Kind::Oscillator(number),
+            Some("xq") if number > 0 => Kind::Spaceship(number),
+            _ => return Err(invalid()),
+        };
+        let cells = decode(code)?;
+        if kind == Kind::StillLife && cells.len() as u64 != number {
+            return Err(ApgcodeError::WrongPopulation(number as usize, cells.len()));
+        }
+        // Stored the way `from_phases` would, so equal objects compare equal.
+        let cells = normalized(cells.into_iter().map(|(x, y)| (x as i64, y as i64)));
+        Ok(Self { kind, cells })
+    }
+}
+
+// Rotations and reflections as matrices acting on column vectors `(x, y)`.
+const ORIENTATIONS: [[[i64; 2]; 2]; 8] = [
+    [[1, 0], [0, 1]],
+    [[0, -1], [1, 0]],
+    [[-1, 0], [0, -1]],
+    [[0, 1], [-1, 0]],
+    [[-1, 0], [0, 1]],
+    [[1, 0], [0, -1]],
+    [[0, 1], [1, 0]],
+    [[0, -1], [-1, 0]],
+];
+
+// Cells moved to the top left corner, in row order.
+fn normalized(cells: impl Iterator<Item = (i64, i64)>) -> Vec<(usize, usize)> {
+    let cells: Vec<_> = cells.collect();
+    let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let mut cells: Vec<_> = cells
+        .into_iter()
+        .map(|(x, y)| ((x - left) as usize, (y - top) as usize))
+        .collect();
+    cells.sort_by_key(|&(x, y)| (y, x));
+    cells
+}
+
+/// Extended Wechsler format of the cells: strips of five rows separated by `z`, one
+/// character per column with the top row as the lowest bit, and runs of empty columns
+/// shortened to `w`, `x` and `y` followed by the length minus four.
+pub fn encode(cells: &[(usize, usize)]) -> String {
+    let width = cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+    let mut columns = vec![vec![0u8; width]; height.div_ceil(STRIP_HEIGHT)];
+    for &(x, y) in cells {
+        columns[y / STRIP_HEIGHT][x] |= 1 << (y % STRIP_HEIGHT);
+    }
+    let strips: Vec<String> = columns
+        .iter()
+        .map(|strip| {
+            let end = strip.iter().rposition(|&c| c != 0).map_or(0, |x| x + 1);
+            let mut out = String::new();
+            let mut zeros = 0;
+            for &column in &strip[..end] {
+                if column == 0 {
+                    zeros += 1;
+                    continue;
+                }
+                push_zeros(&mut out, zeros);
+                zeros = 0;
+                out.push(DIGITS[column as usize] as char);
+            }
+            out
+        })
+        .collect();
+    strips.join("z")
+}
+
+fn push_zeros(out: &mut String, mut zeros: usize) {
+    while zeros > 0 {
+        let run = zeros.min(39);
+        match run {
+            1 => out.push('0'),
+            2 => out.push('w'),
+            3 => out.push('x'),
+            _ => {
+                out.push('y');
+                out.push(DIGITS[run - 4] as char);
+            }
+        }
+        zeros -= run;
+    }
+}
+
+/// Cells of an extended Wechsler code, the part of an apgcode after the `_`.
+pub fn decode(code: &str) -> Result<Vec<(usize, usize)>, ApgcodeError> {
+    let mut cells = Vec::new();
+    let (mut x, mut strip) = (0, 0);
+    let mut chars = code.chars().enumerate().map(|(i, c)| (i + 1, c));
+    while let Some((position, c)) = chars.next() {
+        let digit = |c: char| DIGITS.iter().position(|&d| d as char == c);
+        match c {
+            'w' => x += 2,
+            'x' => x += 3,
+            'y' => match chars.next() {
+                Some((position, c)) => match digit(c) {
+                    Some(run) => x += 4 + run,
+                    None => return Err(ApgcodeError::UnexpectedChar(position, c)),
+                },
+                None => return Err(ApgcodeError::UnexpectedChar(position, c)),
+            },
+            'z' => (x, strip) = (0, strip + 1),
+            c => {
+                let column = digit(c).ok_or(ApgcodeError::UnexpectedChar(position, c))?;
+                cells.extend(
+                    (0..STRIP_HEIGHT)
+                        .filter(|bit| column & (1 << bit) != 0)
+                        .map(|bit| (x, strip * STRIP_HEIGHT + bit)),
+                );
+                x += 1;
+            }
+        }
+    }
+    cells.sort_by_key(|&(x, y)| (y, x));
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        apgcode::{decode, encode, Apgcode, ApgcodeError, Kind},
+        rle,
+        rule::Rule,
+        topology::WrapMode,
+        GameOfLife,
+    };
+
+    #[test]
+    fn encode_test() {
+        assert_eq!(encode(&[(0, 0), (1, 0), (0, 1), (1, 1)]), "33");
+        assert_eq!(encode(&[(0, 0), (3, 0)]), "1w1");
+        assert_eq!(encode(&[(0, 0), (44, 0), (0, 5)]), "1yzy01z1");
+        assert_eq!(decode("1yzy01z1").unwrap(), vec![(0, 0), (44, 0), (0, 5)]);
+        // Empty strips leave consecutive separators.
+        let tall = [(0, 0), (0, 12)];
+        assert_eq!(encode(&tall), "1zz4");
+        assert_eq!(decode("1zz4").unwrap(), tall);
+    }
+
+    #[test]
+    fn canonical_test() {
+        let codes = [
+            ("bo$2bo$3o!", "xq4_153"),
+            ("2o$2o!", "xs4_33"),
+            ("3o!", "xp2_7"),
+            ("b2o$o2bo$b2o!", "xs6_696"),
+            ("2o$obo$bo!", "xs5_253"),
+            ("b3o$3o!", "xp2_7e"),
+        ];
+        for (body, code) in codes {
+            let pattern = rle::parse(&format!("x = 4, y = 3\n{}", body)).unwrap();
+            // Objects are stepped on the plane, so being close to the edges doesn't matter.
+            let mut game = GameOfLife::empty(12, 12, WrapMode::Wrap);
+            pattern
+                .cells
+                .iter()
+                .for_each(|&((x, y), cell)| game.set(x + 8, y + 8, cell));
+            let apgcode = Apgcode::from_game(&game, 16).unwrap();
+            assert_eq!(apgcode.to_string(), code);
+            assert_eq!(code.parse::<Apgcode>().unwrap(), apgcode);
+        }
+        let rpentomino = GameOfLife::from_cells(
+            5,
+            5,
+            WrapMode::NoWrap,
+            [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)],
+        );
+        assert_eq!(Apgcode::from_game(&rpentomino, 100), None);
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        assert_eq!(
+            Apgcode::from_cells(Rule::default(), glider, 4).map(|code| code.kind),
+            Some(Kind::Spaceship(4))
+        );
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(
+            "xs5_33".parse::<Apgcode>(),
+            Err(ApgcodeError::WrongPopulation(5, 4))
+        );
+        assert_eq!(
+            "xp0_7".parse::<Apgcode>(),
+            Err(ApgcodeError::InvalidPrefix("xp0_7".to_string()))
+        );
+        assert_eq!(
+            "yl144_1_16_afb5f3db909e60548f086e22ee3353ac".parse::<Apgcode>(),
+            Err(ApgcodeError::InvalidPrefix(
+                "yl144_1_16_afb5f3db909e60548f086e22ee3353ac".to_string()
+            ))
+        );
+        assert_eq!(
+            "xs4_3A".parse::<Apgcode>(),
+            Err(ApgcodeError::UnexpectedChar(2, 'A'))
+        );
+    }
+}
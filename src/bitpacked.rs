@@ -0,0 +1,336 @@
+//! Bounded engine storing 64 cells per word and stepping them with bitwise adders.
+
+use std::fmt::Display;
+
+use crate::{
+    geometry::Geometry,
+    neighborhood::{Neighborhood, Shape},
+    rule::{Rule, RuleError},
+    topology::{Axis, Topology, TopologyError, WrapMode},
+    Cell,
+};
+
+/// Field that stores 64 cells per word and advances all of them at once, summing the eight
+/// neighbors with bitwise adders. Only runs two-state outer-totalistic rules on the Moore
+/// neighborhood, on a plane, torus or cylinder.
+#[derive(Debug, Clone)]
+pub struct BitPacked {
+    width: usize,
+    height: usize,
+    // Words per row. Bit i of word k holds the cell at x = 64 * k + i, bits past the width
+    // are always zero.
+    words: usize,
+    cells: Vec<u64>,
+    next: Vec<u64>,
+    wrap: WrapMode,
+    rule: Rule,
+    birth: u16,
+    survival: u16,
+}
+
+impl BitPacked {
+    /// Empty field. Only the torus, the plane and cylinders are supported.
+    pub fn new(width: usize, height: usize, wrap: WrapMode) -> Result<Self, TopologyError> {
+        if !matches!(
+            wrap,
+            WrapMode::Wrap | WrapMode::NoWrap | WrapMode::Cylinder(_)
+        ) {
+            return Err(TopologyError::Unsupported(Topology {
+                width,
+                height,
+                wrap,
+            }));
+        }
+        // Rows and words wrap around modulo the size.
+        if width == 0 || height == 0 {
+            return Err(TopologyError::InvalidDimension("0".to_string()));
+        }
+        let words = width.div_ceil(64);
+        let rule = Rule::default();
+        let (birth, survival) = Self::counts(&rule).unwrap_or_default();
+        Ok(Self {
+            width,
+            height,
+            words,
+            cells: vec![0; words * height],
+            next: vec![0; words * height],
+            wrap,
+            rule,
+            birth,
+            survival,
+        })
+    }
+
+    /// Runs the given rule from now on. Only two-state outer totalistic rules on the Moore
+    /// neighborhood are supported.
+    pub fn with_rule(mut self, rule: Rule) -> Result<Self, RuleError> {
+        (self.birth, self.survival) =
+            Self::counts(&rule).ok_or(RuleError::Unsupported("bit-packed"))?;
+        self.rule = rule;
+        Ok(self)
+    }
+
+    /// Rule the field runs.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// How the edges of the field are glued together.
+    pub fn wrap(&self) -> WrapMode {
+        self.wrap
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Cell at the given coordinates, mapped through the topology if they are outside of
+    /// the field. `None` if they don't map onto it.
+    pub fn get(&self, x: isize, y: isize) -> Option<Cell> {
+        let (x, y) = self.wrap.wrap(x, y, self.width, self.height)?;
+        let word = self.cells[y * self.words + x / 64];
+        Some(if word & (1 << (x % 64)) != 0 {
+            Cell::alive()
+        } else {
+            Cell::dead()
+        })
+    }
+
+    /// Whether the cell at the coordinates, mapped like in [`BitPacked::get`], is alive.
+    pub fn is_alive(&self, x: isize, y: isize) -> bool {
+        self.get(x, y).is_some_and(|c| c.is_alive())
+    }
+
+    /// Makes the cell alive if `cell` is, dead otherwise.
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        let word = &mut self.cells[y * self.words + x / 64];
+        if cell.is_alive() {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Advances the field by one generation.
+    pub fn update(&mut self) {
+        let wrap_y = matches!(
+            self.wrap,
+            WrapMode::Wrap | WrapMode::Cylinder(Axis::Horizontal)
+        );
+        let row = |y: isize| -> Option<usize> {
+            match y {
+                y if (0..self.height as isize).contains(&y) => Some(y as usize),
+                _ if wrap_y => Some(y.rem_euclid(self.height as isize) as usize),
+                _ => None,
+            }
+        };
+        let last = match self.width % 64 {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        };
+        for y in 0..self.height {
+            let (above, below) = (row(y as isize - 1), row(y as isize + 1));
+            for k in 0..self.words {
+                let [nw, n, ne] = self.row_neighbors(above, k);
+                let [w, alive, e] = self.row_neighbors(Some(y), k);
+                let [sw, s, se] = self.row_neighbors(below, k);
+                let count = Self::sum([nw, n, ne, w, e, sw, s, se]);
+                let mut next = (0..=8)
+                    .filter(|n| (self.birth | self.survival) & (1 << n) != 0)
+                    .fold(0, |next, n| {
+                        let equal = (0..4).fold(!0, |equal, bit| {
+                            equal
+                                & if n & (1 << bit) != 0 {
+                                    count[bit]
+                                } else {
+                                    !count[bit]
+                                }
+                        });
+                        let born = if self.birth & (1 << n) != 0 {
+                            !alive
+                        } else {
+                            0
+                        };
+                        let survives = if self.survival & (1 << n) != 0 {
+                            alive
+                        } else {
+                            0
+                        };
+                        next | (equal & (born | survives))
+                    });
+                if k + 1 == self.words {
+                    next &= last;
+                }
+                self.next[y * self.words + k] = next;
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.next);
+    }
+
+    // Birth and survival counts of rules that only depend on the number of live Moore
+    // neighbors.
+    fn counts(rule: &Rule) -> Option<(u16, u16)> {
+        let moore = Neighborhood::new(Shape::Moore, 1, false);
+        if rule.states() > 2 || rule.neighborhood().is_some_and(|n| n != moore) {
+            return None;
+        }
+        let mask = |cell: Cell| {
+            (0..=8).fold(0u16, |mask, n| {
+                let configuration = ((1u16 << n) - 1) as u8;
+                mask | u16::from(rule.next(cell, configuration).is_alive()) << n
+            })
+        };
+        let (birth, survival) = (mask(Cell::dead()), mask(Cell::alive()));
+        let totalistic = (0..=u8::MAX).all(|c| {
+            let n = c.count_ones();
+            rule.next(Cell::dead(), c).is_alive() == (birth & (1 << n) != 0)
+                && rule.next(Cell::alive(), c).is_alive() == (survival & (1 << n) != 0)
+        });
+        totalistic.then_some((birth, survival))
+    }
+
+    // Word k of a row, shifted so every bit holds its west neighbor, unshifted, and shifted
+    // so every bit holds its east neighbor.
+    fn row_neighbors(&self, y: Option<usize>, k: usize) -> [u64; 3] {
+        let Some(y) = y else {
+            return [0; 3];
+        };
+        let row = &self.cells[y * self.words..(y + 1) * self.words];
+        let wrap_x = matches!(
+            self.wrap,
+            WrapMode::Wrap | WrapMode::Cylinder(Axis::Vertical)
+        );
+        let end = (self.width - 1) % 64;
+        let word = row[k];
+
+        let mut west = word << 1;
+        if k > 0 {
+            west |= row[k - 1] >> 63;
+        } else if wrap_x {
+            west |= (row[self.words - 1] >> end) & 1;
+        }
+        let mut east = word >> 1;
+        if k + 1 < self.words {
+            east |= row[k + 1] << 63;
+        } else if wrap_x {
+            east |= (row[0] & 1) << end;
+        }
+        [west, word, east]
+    }
+
+    // Adds up eight words bit by bit, returning the four bits of every sum.
+    fn sum(words: [u64; 8]) -> [u64; 4] {
+        let full = |a: u64, b: u64, c: u64| (a ^ b ^ c, (a & b) | (c & (a ^ b)));
+        let half = |a: u64, b: u64| (a ^ b, a & b);
+        let [a, b, c, d, e, f, g, h] = words;
+
+        let (s0, c0) = full(a, b, c);
+        let (s1, c1) = full(d, e, f);
+        let (s2, c2) = half(g, h);
+        let (ones, c3) = full(s0, s1, s2);
+        let (t, c4) = full(c0, c1, c2);
+        let (twos, c5) = half(t, c3);
+        [ones, twos, c4 ^ c5, c4 & c5]
+    }
+}
+
+impl Display for BitPacked {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Geometry::Square.render(f, self.width, self.height, |x, y| {
+            self.get(x as isize, y as isize).unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bitpacked::BitPacked,
+        rule::RuleError,
+        topology::{Axis, TopologyError, WrapMode},
+        GameOfLife,
+    };
+
+    fn packed(game: &GameOfLife) -> BitPacked {
+        let mut packed = BitPacked::new(game.width, game.height, game.wrap)
+            .unwrap()
+            .with_rule(*game.rule())
+            .unwrap();
+        for (i, &c) in game.field.iter().enumerate() {
+            packed.set(i % game.width, i / game.width, c);
+        }
+        packed
+    }
+
+    #[test]
+    fn matches_game_of_life_test() {
+        for wrap in [
+            WrapMode::Wrap,
+            WrapMode::NoWrap,
+            WrapMode::Cylinder(Axis::Horizontal),
+            WrapMode::Cylinder(Axis::Vertical),
+        ] {
+            for (width, height, rule) in [(70, 13, "B3/S23"), (64, 9, "B36/S23"), (5, 7, "B0/S8")] {
+                let mut game =
+                    GameOfLife::new(width, height, wrap).with_rule(rule.parse().unwrap());
+                let mut packed = packed(&game);
+                for _ in 0..20 {
+                    game.update();
+                    packed.update();
+                    assert_eq!(packed.to_string(), game.to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_test() {
+        assert!(BitPacked::new(8, 8, WrapMode::Sphere)
+            .is_err_and(|e| matches!(e, TopologyError::Unsupported(_))));
+        let packed = BitPacked::new(8, 8, WrapMode::Wrap).unwrap();
+        assert_eq!(
+            packed.clone().with_rule("B2-a/S12".parse().unwrap()).err(),
+            Some(RuleError::Unsupported("bit-packed"))
+        );
+        assert!(packed
+            .clone()
+            .with_rule("R1,C0,M0,S2..3,B3..3,NM".parse().unwrap())
+            .is_ok());
+        assert!(packed.with_rule("Brian's Brain".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn large_field_test() {
+        let (width, height) = (300, 200);
+        let mut game = GameOfLife::new(width, height, WrapMode::Wrap);
+        let mut packed = packed(&game);
+        for _ in 0..10 {
+            game.update();
+            packed.update();
+        }
+        assert!((0..height).all(|y| (0..width).all(|x| {
+            let (x, y) = (x as isize, y as isize);
+            packed.is_alive(x, y) == game.is_alive(x, y)
+        })));
+    }
+
+    #[test]
+    fn empty_size_test() {
+        assert_eq!(
+            BitPacked::new(0, 8, WrapMode::Wrap).err(),
+            Some(TopologyError::InvalidDimension("0".to_string()))
+        );
+        assert!(BitPacked::new(8, 0, WrapMode::NoWrap).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+//! Splitting a field into its objects and counting them by apgcode.
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt::Display,
+    hash::Hash,
+    ops::AddAssign,
+};
+
+use crate::{
+    apgcode::Apgcode,
+    classify::Classification,
+    plane::Plane,
+    rule::{Rule, RuleError},
+    Cell, GameOfLife,
+};
+
+// Live cells at most this far apart in both directions belong to the same cluster.
+const CLUSTER_DISTANCE: i64 = 2;
+
+/// Catagolue's name for objects that don't repeat within the period budget.
+pub const UNKNOWN: &str = "zz_UNKNOWN";
+
+/// One object of a field: a cluster of live cells that evolves on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    /// Live cells, unwrapped so that objects crossing the edges of the field stay whole.
+    pub cells: Vec<(i64, i64)>,
+    /// `None` if the object didn't repeat within the period budget.
+    pub apgcode: Option<Apgcode>,
+}
+
+impl Object {
+    /// Apgcode of the object, or `zz_UNKNOWN` if it has none.
+    pub fn code(&self) -> String {
+        self.apgcode
+            .as_ref()
+            .map_or_else(|| UNKNOWN.to_string(), Apgcode::to_string)
+    }
+}
+
+/// Splits the live cells of the field into objects and names each of them.
+///
+/// Cells within distance 2 are clustered first. A cluster that repeats within
+/// `max_period` generations is then split into its islands of touching cells wherever
+/// they evolve the same apart as together, so pseudo objects like two blocks side by
+/// side count as two objects. Dying cells of multistate rules are left out.
+pub fn objects(game: &GameOfLife, max_period: u64) -> Result<Vec<Object>, RuleError> {
+    let starts = game.live_cells().map(|(x, y)| (x as i64, y as i64));
+    let clusters = clusters(starts, |x, y| {
+        let (x, y) = game
+            .wrap
+            .wrap(x as isize, y as isize, game.width, game.height)?;
+        let index = game.index(x, y);
+        game.field[index].is_alive().then_some(index)
+    });
+    name(*game.rule(), clusters, max_period)
+}
+
+/// Splits the live cells of the plane into objects, like [`objects`] does for a field.
+pub fn plane_objects(plane: &Plane, max_period: u64) -> Result<Vec<Object>, RuleError> {
+    // Sorted so objects come out in the same order on every run.
+    let mut starts: Vec<_> = plane.live_cells().collect();
+    starts.sort_unstable_by_key(|&(x, y)| (y, x));
+    let clusters = clusters(starts.into_iter(), |x, y| {
+        plane.is_alive(x, y).then_some((x, y))
+    });
+    name(*plane.rule(), clusters, max_period)
+}
+
+// Splits every cluster into objects and names them.
+fn name(
+    rule: Rule,
+    clusters: Vec<Vec<(i64, i64)>>,
+    max_period: u64,
+) -> Result<Vec<Object>, RuleError> {
+    // Fails early for rules the plane can't run.
+    Plane::new().with_rule(rule)?;
+    let mut objects = Vec::new();
+    for cluster in clusters {
+        let groups = match Classification::from_cells(rule, alive(&cluster), max_period)? {
+            classification if classification.period().is_some() => {
+                let period = classification.period().unwrap_or(1);
+                split(rule, &cluster, period)?
+            }
+            _ => vec![cluster],
+        };
+        objects.extend(groups.into_iter().map(|cells| Object {
+            apgcode: Apgcode::from_cells(rule, cells.iter().copied(), max_period),
+            cells,
+        }));
+    }
+    Ok(objects)
+}
+
+fn alive(cells: &[(i64, i64)]) -> impl Iterator<Item = ((i64, i64), Cell)> + '_ {
+    cells.iter().map(|&p| (p, Cell::alive()))
+}
+
+// Live cells within `CLUSTER_DISTANCE` of each other. `cell` maps a position to the cell
+// it stands for, or `None` if that cell isn't alive, so that positions wrapping onto the
+// same cell of a field are only visited once.
+fn clusters<K: Hash + Eq>(
+    starts: impl Iterator<Item = (i64, i64)>,
+    cell: impl Fn(i64, i64) -> Option<K>,
+) -> Vec<Vec<(i64, i64)>> {
+    let mut seen = HashSet::new();
+    let mut clusters = Vec::new();
+    for (x, y) in starts {
+        if !cell(x, y).is_some_and(|key| seen.insert(key)) {
+            continue;
+        }
+        let mut queue = VecDeque::from([(x, y)]);
+        let mut cluster = Vec::new();
+        while let Some((x, y)) = queue.pop_front() {
+            cluster.push((x, y));
+            for dy in -CLUSTER_DISTANCE..=CLUSTER_DISTANCE {
+                for dx in -CLUSTER_DISTANCE..=CLUSTER_DISTANCE {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if cell(nx, ny).is_some_and(|key| seen.insert(key)) {
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+        }
+        cluster.sort_by_key(|&(x, y)| (y, x));
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+// Groups of touching cells.
+fn islands(cells: &[(i64, i64)]) -> Vec<Vec<(i64, i64)>> {
+    let mut left: HashSet<_> = cells.iter().copied().collect();
+    let mut islands = Vec::new();
+    for &start in cells {
+        if !left.remove(&start) {
+            continue;
+        }
+        let mut island = vec![start];
+        let mut next = 0;
+        while let Some(&(x, y)) = island.get(next) {
+            next += 1;
+            for (dx, dy) in (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))) {
+                if left.remove(&(x + dx, y + dy)) {
+                    island.push((x + dx, y + dy));
+                }
+            }
+        }
+        islands.push(island);
+    }
+    islands
+}
+
+// Whether the two groups of cells evolve the same way apart as together for the given
+// number of generations.
+fn independent(
+    rule: Rule,
+    a: &[(i64, i64)],
+    b: &[(i64, i64)],
+    generations: u64,
+) -> Result<bool, RuleError> {
+    let plane = |cells: &mut dyn Iterator<Item = &(i64, i64)>| {
+        let mut plane = Plane::new().with_rule(rule)?;
+        cells.for_each(|&(x, y)| plane.set(x, y, Cell::alive()));
+        Ok(plane)
+    };
+    let (mut apart_a, mut apart_b) = (plane(&mut a.iter())?, plane(&mut b.iter())?);
+    let mut together = plane(&mut a.iter().chain(b))?;
+    for _ in 0..generations {
+        for plane in [&mut apart_a, &mut apart_b, &mut together] {
+            plane.update();
+        }
+        let mut apart: Vec<_> = apart_a.live_cells().chain(apart_b.live_cells()).collect();
+        let mut joint: Vec<_> = together.live_cells().collect();
+        apart.sort_unstable();
+        joint.sort_unstable();
+        if apart != joint {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Starts from the islands of the cluster and merges any two groups that interact within
+// one period of the cluster.
+fn split(
+    rule: Rule,
+    cluster: &[(i64, i64)],
+    period: u64,
+) -> Result<Vec<Vec<(i64, i64)>>, RuleError> {
+    let mut groups = islands(cluster);
+    'merge: loop {
+        for i in 0..groups.len() {
+            for j in i + 1..groups.len() {
+                if !independent(rule, &groups[i], &groups[j], period)? {
+                    let merged = groups.swap_remove(j);
+                    groups[i].extend(merged);
+                    continue 'merge;
+                }
+            }
+        }
+        break;
+    }
+    for group in &mut groups {
+        group.sort_by_key(|&(x, y)| (y, x));
+    }
+    Ok(groups)
+}
+
+/// Number of objects of each kind by apgcode, which can be added up over many fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Census {
+    counts: BTreeMap<String, u64>,
+}
+
+impl Census {
+    /// Census without any objects.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Census of the objects on the field, see [`objects`].
+    pub fn from_game(game: &GameOfLife, max_period: u64) -> Result<Self, RuleError> {
+        let mut census = Self::new();
+        for object in objects(game, max_period)? {
+            census.add(object.code(), 1);
+        }
+        Ok(census)
+    }
+
+    /// Counts `count` more objects with the given code.
+    pub fn add(&mut self, code: String, count: u64) {
+        *self.counts.entry(code).or_default() += count;
+    }
+
+    /// Number of objects with the given code.
+    pub fn count(&self, code: &str) -> u64 {
+        self.counts.get(code).copied().unwrap_or(0)
+    }
+
+    /// Number of objects of any kind.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Codes with their counts, most common first.
+    pub fn entries(&self) -> Vec<(&str, u64)> {
+        let mut entries: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(code, &count)| (code.as_str(), count))
+            .collect();
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        entries
+    }
+}
+
+impl AddAssign<&Census> for Census {
+    fn add_assign(&mut self, other: &Census) {
+        for (code, &count) in &other.counts {
+            self.add(code.clone(), count);
+        }
+    }
+}
+
+impl Display for Census {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:>10}  object", "count")?;
+        for (code, count) in self.entries() {
+            writeln!(f, "{:>10}  {}", count, code)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        census::{independent, objects, plane_objects, Census, UNKNOWN},
+        plane::Plane,
+        rule::{Rule, RuleError},
+        topology::WrapMode,
+        Cell, GameOfLife,
+    };
+
+    const BLOCK: [(usize, usize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+    // Cells of an object and where to put its top left corner.
+    type Placed<'a> = (&'a [(usize, usize)], (usize, usize));
+
+    fn field(objects: &[Placed]) -> GameOfLife {
+        let cells = objects.iter().flat_map(|&(cells, (dx, dy))| {
+            cells
+                .iter()
+                .map(move |&(x, y)| ((x + dx) % 20, (y + dy) % 16))
+        });
+        GameOfLife::from_cells(20, 16, WrapMode::Wrap, cells)
+    }
+
+    #[test]
+    fn census_test() {
+        let blinker: &[_] = &[(0, 0), (1, 0), (2, 0)];
+        let glider: &[_] = &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        // The second block crosses the corner of the torus.
+        let game = field(&[
+            (&BLOCK, (4, 3)),
+            (&BLOCK, (19, 15)),
+            (blinker, (8, 2)),
+            (glider, (10, 8)),
+        ]);
+        let census = Census::from_game(&game, 16).unwrap();
+        assert_eq!(census.count("xs4_33"), 2);
+        assert_eq!(census.count("xp2_7"), 1);
+        assert_eq!(census.count("xq4_153"), 1);
+        assert_eq!(census.total(), 4);
+        assert_eq!(census.entries()[0], ("xs4_33", 2));
+
+        let mut total = census.clone();
+        total += &census;
+        assert_eq!(total.count("xs4_33"), 4);
+        assert_eq!(
+            total.to_string(),
+            "     count  object\n         4  xs4_33\n         2  xp2_7\n         2  xq4_153\n"
+        );
+    }
+
+    #[test]
+    fn pseudo_object_test() {
+        // Two blocks one cell apart are one cluster but two objects.
+        let game = field(&[(&BLOCK, (2, 2)), (&BLOCK, (5, 2))]);
+        let found = objects(&game, 16).unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|object| object.code() == "xs4_33"));
+
+        // A cell next to a block sparks off births, so they stay together.
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        assert_eq!(
+            independent(Rule::default(), &block, &[(3, 0)], 2),
+            Ok(false)
+        );
+        assert_eq!(independent(Rule::default(), &block, &[(4, 0)], 2), Ok(true));
+        let b0 = "B03/S23".parse().unwrap();
+        assert_eq!(
+            independent(b0, &block, &[(4, 0)], 2),
+            Err(RuleError::Unbounded)
+        );
+        let game = field(&[(&BLOCK, (2, 2)), (&[(0, 0)], (5, 2))]);
+        let found = objects(&game, 16).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code(), UNKNOWN);
+    }
+
+    #[test]
+    fn plane_test() {
+        let mut plane = Plane::new();
+        let cells = BLOCK.iter().map(|&(x, y)| (x as i64 - 1000, y as i64 - 5));
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        for (x, y) in cells.chain(glider.map(|(x, y)| (x + 500, y + (1 << 40)))) {
+            plane.set(x, y, Cell::alive());
+        }
+        let codes: Vec<_> = plane_objects(&plane, 16)
+            .unwrap()
+            .iter()
+            .map(|object| object.code())
+            .collect();
+        assert_eq!(codes, ["xs4_33", "xq4_153"]);
+    }
+}
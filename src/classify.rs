@@ -0,0 +1,278 @@
+//! Telling still lifes, oscillators and spaceships apart by running them on the plane.
+
+use std::fmt::Display;
+
+use crate::{
+    plane::Plane,
+    rule::{Rule, RuleError},
+    soup::Area,
+    Cell, GameOfLife,
+};
+
+/// Speed of a spaceship: how far it moves in one period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Speed {
+    /// Cells moved in one period.
+    pub displacement: (i64, i64),
+    /// Generations it takes to reappear.
+    pub period: u64,
+}
+
+impl Display for Speed {
+    // LifeWiki's notation: `c/4 diagonal`, `2c/5 orthogonal`, or `(2,1)c/6` for oblique
+    // ships.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (dx, dy) = (
+            self.displacement.0.unsigned_abs(),
+            self.displacement.1.unsigned_abs(),
+        );
+        if dx != 0 && dy != 0 && dx != dy {
+            let (a, b) = (dx.max(dy), dx.min(dy));
+            return write!(f, "({},{})c/{}", a, b, self.period);
+        }
+        let distance = dx.max(dy);
+        let divisor = gcd(distance, self.period);
+        let (distance, period) = (distance / divisor, self.period / divisor);
+        if distance > 1 {
+            write!(f, "{}", distance)?;
+        }
+        let direction = if dx == dy { "diagonal" } else { "orthogonal" };
+        write!(f, "c/{} {}", period, direction)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// What a pattern turns out to be when run on the unbounded plane. Only patterns that
+/// return to their very first generation count as periodic, so a pattern that settles
+/// into a still life after a while is unstable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Classification {
+    /// Every cell is dead from this generation on.
+    Extinct {
+        /// First generation without live cells.
+        generation: u64,
+    },
+    /// Unchanged from the first generation on.
+    StillLife,
+    /// Back to the first generation after `period` generations, in the same place.
+    Oscillator {
+        /// Generations until the first one repeats.
+        period: u64,
+    },
+    /// Back to the first generation, but moved.
+    Spaceship(Speed),
+    /// Neither repeated nor died out within the budget.
+    Unstable,
+}
+
+impl Classification {
+    /// Runs the cells under the rule for at most `budget` generations.
+    pub fn from_cells(
+        rule: Rule,
+        cells: impl IntoIterator<Item = ((i64, i64), Cell)>,
+        budget: u64,
+    ) -> Result<Self, RuleError> {
+        Ok(run(rule, cells, budget)?.0)
+    }
+
+    /// Classifies everything on the field as one pattern, run on the plane so that it
+    /// can't interact with itself across the edges.
+    pub fn from_game(game: &GameOfLife, budget: u64) -> Result<Self, RuleError> {
+        let area = Area {
+            x: 0,
+            y: 0,
+            width: game.width,
+            height: game.height,
+        };
+        Self::from_area(game, area, budget)
+    }
+
+    /// Classifies the cells within an area of the field, ignoring everything else.
+    pub fn from_area(game: &GameOfLife, area: Area, budget: u64) -> Result<Self, RuleError> {
+        let cells = (area.y..(area.y + area.height).min(game.height))
+            .flat_map(|y| (area.x..(area.x + area.width).min(game.width)).map(move |x| (x, y)))
+            .map(|(x, y)| ((x as i64, y as i64), game.field[game.index(x, y)]))
+            .filter(|(_, cell)| !cell.is_dead());
+        Self::from_cells(*game.rule(), cells, budget)
+    }
+
+    /// Generations after which the pattern repeats, if it does.
+    pub fn period(&self) -> Option<u64> {
+        match self {
+            Classification::StillLife => Some(1),
+            Classification::Oscillator { period } => Some(*period),
+            Classification::Spaceship(speed) => Some(speed.period),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Classification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Classification::Extinct { generation } => {
+                write!(f, "dies out in generation {}", generation)
+            }
+            Classification::StillLife => write!(f, "still life"),
+            Classification::Oscillator { period } => write!(f, "period {} oscillator", period),
+            Classification::Spaceship(speed) => write!(f, "{} spaceship", speed),
+            Classification::Unstable => write!(f, "unstable"),
+        }
+    }
+}
+
+// Live cells of every generation, oldest first.
+type Phases = Vec<Vec<(i64, i64)>>;
+type Cells = Vec<((i64, i64), Cell)>;
+
+// Cells that aren't dead relative to the top left corner of their bounding box, in row
+// order, and that corner.
+fn normalized(plane: &Plane) -> ((i64, i64), Cells) {
+    let Some(bounds) = plane.bounding_box() else {
+        return ((0, 0), Vec::new());
+    };
+    let mut cells: Vec<_> = plane
+        .cells()
+        .map(|((x, y), cell)| ((x - bounds.min_x, y - bounds.min_y), cell))
+        .collect();
+    cells.sort_by_key(|&((x, y), _)| (y, x));
+    ((bounds.min_x, bounds.min_y), cells)
+}
+
+/// Classification along with the live cells of every generation before the pattern
+/// repeated or the budget ran out.
+pub(crate) fn run(
+    rule: Rule,
+    cells: impl IntoIterator<Item = ((i64, i64), Cell)>,
+    budget: u64,
+) -> Result<(Classification, Phases), RuleError> {
+    let mut plane = Plane::new().with_rule(rule)?;
+    cells
+        .into_iter()
+        .for_each(|((x, y), cell)| plane.set(x, y, cell));
+    let (start, first) = normalized(&plane);
+    let mut phases = vec![plane.live_cells().collect::<Vec<_>>()];
+    if first.is_empty() {
+        return Ok((Classification::Extinct { generation: 0 }, phases));
+    }
+    for generation in 1..=budget {
+        plane.update();
+        let (corner, cells) = normalized(&plane);
+        if cells.is_empty() {
+            return Ok((Classification::Extinct { generation }, phases));
+        }
+        if cells == first {
+            let displacement = (corner.0 - start.0, corner.1 - start.1);
+            let classification = match generation {
+                _ if displacement != (0, 0) => Classification::Spaceship(Speed {
+                    displacement,
+                    period: generation,
+                }),
+                1 => Classification::StillLife,
+                period => Classification::Oscillator { period },
+            };
+            return Ok((classification, phases));
+        }
+        phases.push(plane.live_cells().collect());
+    }
+    Ok((Classification::Unstable, phases))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        classify::{Classification, Speed},
+        rule::Rule,
+        soup::Area,
+        topology::WrapMode,
+        Cell, GameOfLife,
+    };
+
+    fn classify(cells: &[(i64, i64)], budget: u64) -> Classification {
+        let cells = cells.iter().map(|&p| (p, Cell::alive()));
+        Classification::from_cells(Rule::default(), cells, budget).unwrap()
+    }
+
+    #[test]
+    fn classification_test() {
+        assert_eq!(
+            classify(&[(0, 0), (1, 0), (0, 1), (1, 1)], 10),
+            Classification::StillLife
+        );
+        assert_eq!(
+            classify(&[(0, 0), (1, 0), (2, 0)], 10),
+            Classification::Oscillator { period: 2 }
+        );
+        let glider = classify(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], 10);
+        assert_eq!(
+            glider,
+            Classification::Spaceship(Speed {
+                displacement: (1, 1),
+                period: 4
+            })
+        );
+        assert_eq!(glider.to_string(), "c/4 diagonal spaceship");
+        let lwss = [
+            (1, 0),
+            (4, 0),
+            (0, 1),
+            (0, 2),
+            (4, 2),
+            (0, 3),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+        ];
+        assert_eq!(classify(&lwss, 10).to_string(), "c/2 orthogonal spaceship");
+        assert_eq!(
+            classify(&[(0, 0), (1, 1)], 10),
+            Classification::Extinct { generation: 1 }
+        );
+        let rpentomino = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+        assert_eq!(classify(&rpentomino, 100), Classification::Unstable);
+        assert_eq!(Classification::Unstable.period(), None);
+    }
+
+    #[test]
+    fn speed_test() {
+        let speed = |displacement, period| Speed {
+            displacement,
+            period,
+        };
+        assert_eq!(speed((0, -2), 5).to_string(), "2c/5 orthogonal");
+        assert_eq!(speed((-3, 0), 6).to_string(), "c/2 orthogonal");
+        assert_eq!(speed((-1, 2), 6).to_string(), "(2,1)c/6");
+    }
+
+    #[test]
+    fn area_test() {
+        // A block and a blinker side by side.
+        let cells = [(1, 1), (2, 1), (1, 2), (2, 2), (6, 2), (7, 2), (8, 2)];
+        let game = GameOfLife::from_cells(10, 5, WrapMode::Wrap, cells);
+        let area = |x, width| Area {
+            x,
+            y: 0,
+            width,
+            height: 5,
+        };
+        let classify = |area| Classification::from_area(&game, area, 10).unwrap();
+        assert_eq!(classify(area(0, 4)), Classification::StillLife);
+        assert_eq!(
+            classify(area(5, 5)),
+            Classification::Oscillator { period: 2 }
+        );
+        assert_eq!(
+            Classification::from_game(&game, 10),
+            Ok(Classification::Oscillator { period: 2 })
+        );
+        let b0 = game.with_rule("B03/S23".parse().unwrap());
+        assert!(Classification::from_game(&b0, 10).is_err());
+    }
+}
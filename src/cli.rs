@@ -0,0 +1,351 @@
+use std::{fmt::Display, path::PathBuf, time::Duration};
+
+use game_of_life::{
+    pattern::{Format, PatternError},
+    rule::{Rule, RuleError},
+    soup::{SoupError, Symmetry},
+    topology::{Axis, Topology, TopologyError, WrapMode},
+};
+
+pub const USAGE: &str = "\
+Usage: game_of_life [OPTIONS] [PATTERN]
+
+Runs a random soup, or the pattern file PATTERN (RLE, .cells, Life 1.05/1.06 or
+macrocell) centered on the field. With --search, runs a batch of soups named by a seed
+string each until it settles, and reports what they left behind.
+
+Options:
+      --width N          Width of the field [default: 30]
+      --height N         Height of the field [default: 30]
+      --topology GRID    Golly bounded grid setting the wrap mode and size, e.g. T30,20,
+                         P30,20 (no wrap), K30*,20, C30,20 or S30 [default: T30,30]
+      --rule RULE        Rule to run, overriding the pattern's own [default: B3/S23]
+      --seed N           Seed of the random soup [default: random]
+      --density P        Probability of a soup cell being alive [default: 0.5]
+      --symmetry SYM     apgsearch symmetry of the soup, e.g. C1, D2_+1 [default: C1]
+      --fps N            Generations shown per second [default: 30]
+      --generations N    Stop after N generations [default: when the field is empty]
+      --input FILE       Pattern file to run, same as PATTERN
+      --output FILE      Write the last generation to FILE
+      --format FORMAT    Format of the output: rle, cells, life105, life106 or mc
+                         [default: from the output extension, else rle]
+      --ignore-cycles    Keep running when the field repeats an earlier generation
+      --census           Count the objects left on the field by apgcode
+      --search N         Run N soups on the unbounded plane without drawing, each
+                         filling a --width by --height area [default: 16x16];
+                         --generations caps each soup [default: 10000]
+      --report-dir DIR   Write the search report and an RLE file of each soup with
+                         rare objects to DIR
+      --root STRING      Seed string of the search, soup n is STRING followed by n
+                         [default: random]
+      --threads N        Threads running soups [default: one per core]
+      --headless         Step without drawing, then write the last generation to the
+                         output or to stdout; needs --generations
+  -h, --help             Print this help";
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue(String, String),
+    UnexpectedArgument(String),
+    Rule(RuleError),
+    Topology(TopologyError),
+    Symmetry(SoupError),
+    Format(PatternError),
+    MissingGenerations,
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option '{}'", option),
+            CliError::MissingValue(option) => write!(f, "option '{}' needs a value", option),
+            CliError::InvalidValue(option, value) => {
+                write!(f, "invalid value '{}' for option '{}'", value, option)
+            }
+            CliError::UnexpectedArgument(s) => write!(f, "unexpected argument '{}'", s),
+            CliError::Rule(e) => write!(f, "{}", e),
+            CliError::Topology(e) => write!(f, "{}", e),
+            CliError::Symmetry(e) => write!(f, "{}", e),
+            CliError::Format(e) => write!(f, "{}", e),
+            CliError::MissingGenerations => write!(f, "--headless needs --generations"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub topology: Topology,
+    /// Whether the size of the field was asked for rather than left at the default.
+    pub size_given: bool,
+    pub rule: Option<Rule>,
+    pub seed: Option<u64>,
+    pub density: f64,
+    pub symmetry: Symmetry,
+    pub fps: f64,
+    pub generations: Option<u64>,
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub format: Option<Format>,
+    pub ignore_cycles: bool,
+    pub census: bool,
+    pub search: Option<u64>,
+    pub report_dir: Option<PathBuf>,
+    pub root: Option<String>,
+    pub threads: Option<usize>,
+    pub headless: bool,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            topology: Topology {
+                width: 30,
+                height: 30,
+                wrap: WrapMode::Wrap,
+            },
+            size_given: false,
+            rule: None,
+            seed: None,
+            density: 0.5,
+            symmetry: Symmetry::C1,
+            fps: 30.0,
+            generations: None,
+            input: None,
+            output: None,
+            format: None,
+            ignore_cycles: false,
+            census: false,
+            search: None,
+            report_dir: None,
+            root: None,
+            threads: None,
+            headless: false,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    /// Parses the arguments after the program name. Options take their value either as the
+    /// next argument or after `=`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        let (mut width, mut height) = (None, None);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                if options.input.is_some() {
+                    return Err(CliError::UnexpectedArgument(arg));
+                }
+                options.input = Some(PathBuf::from(arg));
+                continue;
+            }
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            match name.as_str() {
+                "-h" | "--help" => options.help = true,
+                "--headless" => options.headless = true,
+                "--ignore-cycles" => options.ignore_cycles = true,
+                "--census" => options.census = true,
+                "--width" | "--height" | "--topology" | "--rule" | "--seed" | "--density"
+                | "--symmetry" | "--fps" | "--generations" | "--input" | "--output"
+                | "--format" | "--search" | "--report-dir" | "--root" | "--threads" => {
+                    let value = inline
+                        .or_else(|| args.next())
+                        .ok_or_else(|| CliError::MissingValue(name.clone()))?;
+                    let invalid = || CliError::InvalidValue(name.clone(), value.clone());
+                    match name.as_str() {
+                        "--width" => width = Some(positive(&value).ok_or_else(invalid)?),
+                        "--height" => height = Some(positive(&value).ok_or_else(invalid)?),
+                        "--topology" => {
+                            options.topology = value.parse().map_err(CliError::Topology)?;
+                            options.size_given = true;
+                        }
+                        "--rule" => options.rule = Some(value.parse().map_err(CliError::Rule)?),
+                        "--seed" => options.seed = Some(value.parse().map_err(|_| invalid())?),
+                        "--density" => {
+                            options.density = value
+                                .parse()
+                                .ok()
+                                .filter(|density| (0.0..=1.0).contains(density))
+                                .ok_or_else(invalid)?
+                        }
+                        "--symmetry" => {
+                            options.symmetry = value.parse().map_err(CliError::Symmetry)?
+                        }
+                        "--fps" => {
+                            options.fps = value
+                                .parse()
+                                .ok()
+                                // The time per frame has to fit in a `Duration`.
+                                .filter(|&fps: &f64| {
+                                    fps > 0.0
+                                        && fps.is_finite()
+                                        && Duration::try_from_secs_f64(1.0 / fps).is_ok()
+                                })
+                                .ok_or_else(invalid)?
+                        }
+                        "--generations" => {
+                            options.generations = Some(value.parse().map_err(|_| invalid())?)
+                        }
+                        "--input" => options.input = Some(PathBuf::from(value)),
+                        "--output" => options.output = Some(PathBuf::from(value)),
+                        "--search" => options.search = Some(value.parse().map_err(|_| invalid())?),
+                        "--report-dir" => options.report_dir = Some(PathBuf::from(value)),
+                        "--root" => options.root = Some(value),
+                        "--threads" => {
+                            options.threads = Some(positive(&value).ok_or_else(invalid)?)
+                        }
+                        _ => options.format = Some(value.parse().map_err(CliError::Format)?),
+                    }
+                }
+                _ => return Err(CliError::UnknownOption(arg)),
+            }
+        }
+        options.size_given |= width.is_some() || height.is_some();
+        options.resize(width, height)?;
+        if options.headless
+            && options.search.is_none()
+            && options.generations.is_none()
+            && !options.help
+        {
+            return Err(CliError::MissingGenerations);
+        }
+        Ok(options)
+    }
+
+    /// Format of the output: as asked for, else from the extension of the output file.
+    pub fn output_format(&self) -> Format {
+        self.format
+            .or_else(|| self.output.as_deref().and_then(Format::from_path))
+            .unwrap_or(Format::Rle)
+    }
+
+    // Applies `--width` and `--height` on top of the topology. Cylinders leave the size of
+    // their unwrapped axis open, which then defaults to 30 like the rest of the field.
+    fn resize(&mut self, width: Option<usize>, height: Option<usize>) -> Result<(), CliError> {
+        let topology = &mut self.topology;
+        let open = |size: usize| if size == 0 { 30 } else { size };
+        topology.width = width.unwrap_or(open(topology.width));
+        topology.height = height.unwrap_or(open(topology.height));
+        let valid = match topology.wrap {
+            WrapMode::Sphere => topology.width == topology.height,
+            WrapMode::ShiftedTorus(Axis::Horizontal, shift) => {
+                shift.unsigned_abs() < topology.width
+            }
+            WrapMode::ShiftedTorus(Axis::Vertical, shift) => shift.unsigned_abs() < topology.height,
+            _ => true,
+        };
+        if !valid {
+            let size = format!("{}x{}", topology.width, topology.height);
+            return Err(CliError::InvalidValue("--topology".to_string(), size));
+        }
+        Ok(())
+    }
+}
+
+fn positive(s: &str) -> Option<usize> {
+    s.parse().ok().filter(|&n| n > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use game_of_life::{
+        pattern::Format,
+        topology::{Axis, WrapMode},
+    };
+
+    use crate::cli::{CliError, Options};
+
+    fn parse(args: &str) -> Result<Options, CliError> {
+        Options::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(parse(""), Ok(Options::default()));
+        let options = parse(
+            "--width 64 --height=48 --topology K10*,10 --rule B36/S23 --seed 7 --density 0.3 \
+             --symmetry D2_+1 --fps 10 --generations 100 --output out.cells --ignore-cycles --census --headless glider.rle",
+        )
+        .unwrap();
+        assert_eq!((options.topology.width, options.topology.height), (64, 48));
+        assert_eq!(options.topology.wrap, WrapMode::Klein(Axis::Horizontal));
+        assert_eq!(
+            options.rule.map(|rule| rule.to_string()),
+            Some("B36/S23".to_string())
+        );
+        assert_eq!(
+            (options.seed, options.density, options.fps),
+            (Some(7), 0.3, 10.0)
+        );
+        assert_eq!(options.symmetry.to_string(), "D2_+1");
+        assert_eq!(options.generations, Some(100));
+        assert_eq!(options.input, Some(PathBuf::from("glider.rle")));
+        assert_eq!(options.output_format(), Format::Plaintext);
+        assert!(options.headless && options.ignore_cycles && options.census);
+
+        let cylinder = parse("--topology T20,0 --format mc").unwrap();
+        assert_eq!(
+            (cylinder.topology.width, cylinder.topology.height),
+            (20, 30)
+        );
+        assert_eq!(cylinder.output_format(), Format::Macrocell);
+        assert!(parse("-h --headless").unwrap().help);
+
+        let search =
+            parse("--search 1000 --report-dir finds --root=abc_ --threads 4 --headless").unwrap();
+        assert_eq!(search.search, Some(1000));
+        assert_eq!(search.report_dir, Some(PathBuf::from("finds")));
+        assert!(!search.size_given);
+        assert!(parse("--search 10 --height 20").unwrap().size_given);
+        assert_eq!(search.output, None);
+        assert_eq!(search.root.as_deref(), Some("abc_"));
+        assert_eq!(search.threads, Some(4));
+    }
+
+    #[test]
+    fn validation_test() {
+        let invalid = |option: &str, value: &str| {
+            Err(CliError::InvalidValue(
+                option.to_string(),
+                value.to_string(),
+            ))
+        };
+        assert_eq!(parse("--width 0"), invalid("--width", "0"));
+        assert_eq!(parse("--density 1.5"), invalid("--density", "1.5"));
+        assert_eq!(parse("--fps -1"), invalid("--fps", "-1"));
+        assert_eq!(parse("--fps 1e-20"), invalid("--fps", "1e-20"));
+        assert_eq!(parse("--seed x"), invalid("--seed", "x"));
+        assert_eq!(parse("--threads 0"), invalid("--threads", "0"));
+        assert_eq!(
+            parse("--topology S10 --width 20"),
+            invalid("--topology", "20x10")
+        );
+        assert_eq!(
+            parse("--rule"),
+            Err(CliError::MissingValue("--rule".to_string()))
+        );
+        assert_eq!(
+            parse("--speed 2"),
+            Err(CliError::UnknownOption("--speed".to_string()))
+        );
+        assert_eq!(
+            parse("a b"),
+            Err(CliError::UnexpectedArgument("b".to_string()))
+        );
+        assert_eq!(parse("--headless"), Err(CliError::MissingGenerations));
+        assert!(matches!(parse("--rule B9"), Err(CliError::Rule(_))));
+        assert!(matches!(parse("--topology X1"), Err(CliError::Topology(_))));
+        assert!(matches!(parse("--format gif"), Err(CliError::Format(_))));
+    }
+}
@@ -0,0 +1,83 @@
+//! Square, hexagonal and triangular layouts of the cells of a field.
+
+use std::fmt::Write;
+
+use crate::Cell;
+
+/// How cells of the rectangular field are laid out in the plane. Cells are always stored
+/// row-major, so `GameOfLife::index` is the same for every geometry:
+///
+/// - `Hexagonal` uses axial coordinates like Golly: the neighbors of (x, y) are the Moore
+///   neighbors except (x + 1, y - 1) and (x - 1, y + 1).
+/// - `Triangular` alternates triangles in each row, (x, y) pointing up when x + y is even.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Geometry {
+    /// Square cells with eight neighbors.
+    Square,
+    /// Hexagons, drawn as rows shifted by half a cell.
+    Hexagonal,
+    /// Triangles pointing up and down in turn.
+    Triangular,
+}
+
+impl Geometry {
+    /// Whether the triangle at (x, y) points up.
+    pub fn points_up(x: isize, y: isize) -> bool {
+        (x + y).rem_euclid(2) == 0
+    }
+
+    /// Draws a `width * height` field the way its cells are laid out in this geometry.
+    pub fn render(
+        &self,
+        f: &mut impl Write,
+        width: usize,
+        height: usize,
+        cell: impl Fn(usize, usize) -> Cell,
+    ) -> std::fmt::Result {
+        (0..height).try_for_each(|y| {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            if *self == Geometry::Hexagonal {
+                write!(f, "{:1$}", "", height - 1 - y)?;
+            }
+            (0..width).try_for_each(|x| {
+                let c = cell(x, y);
+                match self {
+                    Geometry::Square => write!(f, "{}", c),
+                    Geometry::Hexagonal if x + 1 < width => write!(f, "{} ", c),
+                    Geometry::Hexagonal => write!(f, "{}", c),
+                    Geometry::Triangular if c.is_alive() => {
+                        let up = Geometry::points_up(x as isize, y as isize);
+                        write!(f, "{}", if up { '^' } else { 'v' })
+                    }
+                    Geometry::Triangular => write!(f, "{}", c),
+                }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{geometry::Geometry, Cell};
+
+    #[test]
+    fn render_test() {
+        let cell = |x: usize, y: usize| {
+            if x >= y {
+                Cell::alive()
+            } else {
+                Cell::dead()
+            }
+        };
+        let render = |geometry: Geometry| {
+            let mut s = String::new();
+            geometry.render(&mut s, 3, 2, cell).unwrap();
+            s
+        };
+        assert_eq!(render(Geometry::Square), "###\n ##");
+        assert_eq!(render(Geometry::Hexagonal), " # # #\n  # #");
+        assert_eq!(render(Geometry::Triangular), "^v^\n ^v");
+    }
+}
@@ -0,0 +1,749 @@
+//! Gosper's HashLife: an unbounded quadtree universe that steps by powers of two.
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    geometry::Geometry,
+    hensel,
+    macrocell::{MacroNode, Macrocell},
+    plane::BoundingBox,
+    rule::{Rule, RuleError},
+    Cell,
+};
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+const DEFAULT_MAX_NODES: usize = 1 << 22;
+
+// Square of 2^level cells. Leaves are the two level 0 nodes `DEAD` and `ALIVE`, every other
+// node is made of four quadrants in `nw, ne, sw, se` order.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    children: [NodeId; 4],
+    population: u64,
+}
+
+/// Gosper's HashLife: identical squares are shared as one canonical node and the future of
+/// every node is memoized, so regular patterns can be advanced by astronomically many
+/// generations. Only two-state rules on the Moore neighborhood are supported.
+#[derive(Debug, Clone)]
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    // Coordinates of the top left cell of the root. Stepping by 2^63 generations takes a
+    // root of 2^66 cells, so they don't fit in an i64.
+    origin: (i128, i128),
+    rule: Rule,
+    generation: u128,
+    max_nodes: usize,
+    // Node count at which the advance in progress gives up, see `step_pow2`.
+    limit: usize,
+}
+
+impl HashLife {
+    /// Empty universe running B3/S23.
+    pub fn new() -> Self {
+        let leaf = |population| Node {
+            level: 0,
+            children: [DEAD; 4],
+            population,
+        };
+        let mut life = Self {
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: (-4, -4),
+            rule: Rule::default(),
+            generation: 0,
+            max_nodes: DEFAULT_MAX_NODES,
+            limit: usize::MAX,
+        };
+        life.root = life.empty_node(3);
+        life
+    }
+
+    /// Runs the given rule from now on. Only two-state rules on the Moore neighborhood
+    /// without B0 are supported.
+    pub fn with_rule(mut self, rule: Rule) -> Result<Self, RuleError> {
+        if rule.neighborhood().is_some() || rule.states() > 2 {
+            return Err(RuleError::Unsupported("HashLife"));
+        }
+        if rule.next(Cell::dead(), 0).is_alive() {
+            return Err(RuleError::Unbounded);
+        }
+        self.rule = rule;
+        self.results.clear();
+        Ok(self)
+    }
+
+    /// Number of nodes the cache may hold before unreachable ones are collected. A step
+    /// that needs more nodes than that is split into smaller steps, so a cache too small
+    /// for the pattern makes stepping slow.
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Universe holding the quadtree of a macrocell file, with its rule and generation.
+    pub fn from_macrocell(macrocell: &Macrocell) -> Result<Self, RuleError> {
+        let mut life = Self::new().with_rule(macrocell.rule.unwrap_or_default())?;
+        life.generation = macrocell.generation;
+        if macrocell.nodes.is_empty() {
+            return Ok(life);
+        }
+        // Node ids by position in the file, starting with the empty square.
+        let mut ids = vec![DEAD];
+        for node in &macrocell.nodes {
+            let leaf = |state: u8| match state {
+                0 => Ok(DEAD),
+                1 => Ok(ALIVE),
+                _ => Err(RuleError::Unsupported("HashLife")),
+            };
+            let id = match *node {
+                MacroNode::Bits(rows) => {
+                    let cell =
+                        |x: usize, y: usize| if rows[y] & (1 << x) != 0 { ALIVE } else { DEAD };
+                    life.build(3, 0, 0, &cell)
+                }
+                MacroNode::States(states) => {
+                    let children = [
+                        leaf(states[0])?,
+                        leaf(states[1])?,
+                        leaf(states[2])?,
+                        leaf(states[3])?,
+                    ];
+                    life.join(children)
+                }
+                MacroNode::Inner(level, children) => {
+                    let empty = life.empty_node(level - 1);
+                    let children =
+                        children.map(|child| if child == 0 { empty } else { ids[child] });
+                    life.join(children)
+                }
+            };
+            ids.push(id);
+        }
+        life.root = ids[macrocell.nodes.len()];
+        let offset = 1i128 << (life.level(life.root) - 1);
+        life.origin = (-offset, -offset);
+        while life.level(life.root) < 3 {
+            life.expand();
+        }
+        Ok(life)
+    }
+
+    /// Quadtree of the universe in the macrocell format, sharing nodes the same way.
+    pub fn to_macrocell(&self) -> Macrocell {
+        let mut macrocell = Macrocell {
+            rule: Some(self.rule),
+            generation: self.generation,
+            ..Macrocell::default()
+        };
+        // The root always stays centered on the origin, as the format expects.
+        let mut written = HashMap::new();
+        self.write_node(self.root, &mut macrocell.nodes, &mut written);
+        macrocell
+    }
+
+    /// Rule the universe runs.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Number of generations stepped so far.
+    pub fn generation(&self) -> u128 {
+        self.generation
+    }
+
+    /// Number of nodes in the cache, live or not.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of live cells.
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    /// Cell at the given coordinates, dead outside of the tree.
+    pub fn get(&self, x: i64, y: i64) -> Cell {
+        let level = self.level(self.root);
+        let (x, y) = (x as i128 - self.origin.0, y as i128 - self.origin.1);
+        if x < 0 || y < 0 || x >> level != 0 || y >> level != 0 {
+            return Cell::dead();
+        }
+        let mut node = self.root;
+        for bit in (0..level).rev() {
+            let quadrant = ((y >> bit) & 1) * 2 + ((x >> bit) & 1);
+            node = self.nodes[node as usize].children[quadrant as usize];
+        }
+        if node == ALIVE {
+            Cell::alive()
+        } else {
+            Cell::dead()
+        }
+    }
+
+    /// Whether the cell at the coordinates is alive.
+    pub fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.get(x, y).is_alive()
+    }
+
+    /// Makes the cell alive if `cell` is, dead otherwise, growing the tree to reach it.
+    pub fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        while !self.contains(x, y) {
+            self.expand();
+        }
+        let leaf = if cell.is_alive() { ALIVE } else { DEAD };
+        let (x, y) = (x as i128 - self.origin.0, y as i128 - self.origin.1);
+        self.root = self.set_node(self.root, x as u128, y as u128, leaf);
+    }
+
+    /// Bounds of the live cells, clamped to the range of `i64`.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounds(self.root, self.origin.0, self.origin.1)
+    }
+
+    /// Live cells, leaving out any beyond the range of `i64`.
+    pub fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::with_capacity(self.population() as usize);
+        self.collect_cells(self.root, self.origin.0, self.origin.1, &mut cells);
+        cells
+    }
+
+    /// Advances the universe by one generation.
+    pub fn update(&mut self) {
+        self.step(1);
+    }
+
+    /// Advances the universe by any number of generations, in steps of powers of two.
+    pub fn step(&mut self, generations: u64) {
+        (0..64)
+            .filter(|j| generations & (1 << j) != 0)
+            .for_each(|j| self.step_pow2(j));
+    }
+
+    /// Advances the universe by 2^j generations at once.
+    pub fn step_pow2(&mut self, j: u8) {
+        while self.level(self.root) < j + 3 || !self.is_padded(self.root) {
+            self.expand();
+        }
+        if self.nodes.len() > self.max_nodes {
+            self.collect_garbage();
+        }
+        let mut root = self.advance_within_limit(j);
+        if root.is_none() {
+            // The cache may have been full of garbage from earlier steps, so try again from
+            // a clean one. Failing that, two half steps need fewer nodes at once.
+            let before = self.nodes.len();
+            self.collect_garbage();
+            if self.nodes.len() < before {
+                root = self.advance_within_limit(j);
+            }
+        }
+        let Some(root) = root else {
+            self.collect_garbage();
+            self.step_pow2(j - 1);
+            self.step_pow2(j - 1);
+            return;
+        };
+        let offset = 1i128 << (self.level(self.root) - 2);
+        self.root = root;
+        self.origin = (self.origin.0 + offset, self.origin.1 + offset);
+        self.generation += 1 << j;
+        self.shrink();
+    }
+
+    fn advance_within_limit(&mut self, j: u8) -> Option<NodeId> {
+        // A pattern that outgrows the cache on its own still gets room to advance, and a
+        // single generation always finishes.
+        self.limit = match j {
+            0 => usize::MAX,
+            _ => self.max_nodes.max(2 * self.nodes.len()),
+        };
+        self.advance(self.root, j)
+    }
+
+    /// Drops every node and memoized result that the current pattern no longer refers to.
+    pub fn collect_garbage(&mut self) {
+        let old = std::mem::take(&mut self.nodes);
+        self.nodes = old[..2].to_vec();
+        self.index.clear();
+        self.results.clear();
+        self.empty = vec![DEAD];
+        let mut copied = HashMap::new();
+        self.root = self.copy_from(&old, self.root, &mut copied);
+    }
+
+    fn copy_from(
+        &mut self,
+        old: &[Node],
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if id <= ALIVE {
+            return id;
+        }
+        if let Some(&new) = copied.get(&id) {
+            return new;
+        }
+        let [nw, ne, sw, se] = old[id as usize].children;
+        let children = [
+            self.copy_from(old, nw, copied),
+            self.copy_from(old, ne, copied),
+            self.copy_from(old, sw, copied),
+            self.copy_from(old, se, copied),
+        ];
+        let new = self.join(children);
+        copied.insert(id, new);
+        new
+    }
+
+    // Square of 2^level cells whose top left corner is at `x, y` of a cell function.
+    fn build(
+        &mut self,
+        level: u8,
+        x: usize,
+        y: usize,
+        cell: &impl Fn(usize, usize) -> NodeId,
+    ) -> NodeId {
+        if level == 0 {
+            return cell(x, y);
+        }
+        let half = 1 << (level - 1);
+        let children = [(0, 0), (half, 0), (0, half), (half, half)]
+            .map(|(dx, dy)| self.build(level - 1, x + dx, y + dy, cell));
+        self.join(children)
+    }
+
+    // Appends the node and the nodes below it that aren't written yet, and returns its
+    // position in the file.
+    fn write_node(
+        &self,
+        id: NodeId,
+        nodes: &mut Vec<MacroNode>,
+        written: &mut HashMap<NodeId, usize>,
+    ) -> usize {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return 0;
+        }
+        if let Some(&position) = written.get(&id) {
+            return position;
+        }
+        let macro_node = if node.level == 3 {
+            let mut rows = [0; 8];
+            for (y, row) in rows.iter_mut().enumerate() {
+                for x in 0..8 {
+                    let quadrant = self.children(id)[(y / 4) * 2 + x / 4];
+                    let pair = self.children(quadrant)[((y / 2) % 2) * 2 + (x / 2) % 2];
+                    if self.children(pair)[(y % 2) * 2 + x % 2] == ALIVE {
+                        *row |= 1 << x;
+                    }
+                }
+            }
+            MacroNode::Bits(rows)
+        } else {
+            MacroNode::Inner(
+                node.level,
+                node.children
+                    .map(|child| self.write_node(child, nodes, written)),
+            )
+        };
+        nodes.push(macro_node);
+        written.insert(id, nodes.len());
+        nodes.len()
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.nodes[id as usize].level
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.nodes[id as usize].children
+    }
+
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node {
+            level: self.level(children[0]) + 1,
+            children,
+            population: children
+                .iter()
+                .map(|&c| self.nodes[c as usize].population)
+                .sum(),
+        });
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap();
+            let node = self.join([below; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let size = 1i128 << self.level(self.root);
+        (self.origin.0..self.origin.0 + size).contains(&(x as i128))
+            && (self.origin.1..self.origin.1 + size).contains(&(y as i128))
+    }
+
+    // Doubles the size of the root while keeping it centered on the same cells.
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let empty = self.empty_node(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(children);
+        let offset = 1i128 << (level - 1);
+        self.origin = (self.origin.0 - offset, self.origin.1 - offset);
+    }
+
+    fn shrink(&mut self) {
+        while self.level(self.root) > 3 && self.is_centered(self.root) {
+            let offset = 1i128 << (self.level(self.root) - 2);
+            self.root = self.centre(self.root);
+            self.origin = (self.origin.0 + offset, self.origin.1 + offset);
+        }
+    }
+
+    // Whether all live cells are within the central square of half the size.
+    fn is_centered(&self, id: NodeId) -> bool {
+        let [nw, ne, sw, se] = self.children(id);
+        let inner = |quadrant: NodeId, corner: usize| {
+            let child = self.children(quadrant)[corner];
+            self.nodes[quadrant as usize].population == self.nodes[child as usize].population
+        };
+        inner(nw, 3) && inner(ne, 2) && inner(sw, 1) && inner(se, 0)
+    }
+
+    // Whether all live cells are within the central square of a quarter of the size, so
+    // that they can't grow out of the central half while the node is advanced.
+    fn is_padded(&self, id: NodeId) -> bool {
+        let [nw, ne, sw, se] = self.children(id);
+        let inner = |quadrant: NodeId, corner: usize| {
+            let child = self.children(quadrant)[corner];
+            let grandchild = self.children(child)[corner];
+            self.nodes[quadrant as usize].population == self.nodes[grandchild as usize].population
+        };
+        inner(nw, 3) && inner(ne, 2) && inner(sw, 1) && inner(se, 0)
+    }
+
+    fn set_node(&mut self, id: NodeId, x: u128, y: u128, leaf: NodeId) -> NodeId {
+        let level = self.level(id);
+        if level == 0 {
+            return leaf;
+        }
+        let half = 1 << (level - 1);
+        let quadrant = usize::from(y >= half) * 2 + usize::from(x >= half);
+        let mut children = self.children(id);
+        children[quadrant] = self.set_node(children[quadrant], x % half, y % half, leaf);
+        self.join(children)
+    }
+
+    fn bounds(&self, id: NodeId, x: i128, y: i128) -> Option<BoundingBox> {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            let clamp = |v: i128| v.clamp(i64::MIN.into(), i64::MAX.into()) as i64;
+            return Some(BoundingBox::new(clamp(x), clamp(y)));
+        }
+        let half = 1 << (node.level - 1);
+        [(0, 0), (half, 0), (0, half), (half, half)]
+            .into_iter()
+            .zip(node.children)
+            .filter_map(|((dx, dy), child)| self.bounds(child, x + dx, y + dy))
+            .reduce(|mut a, b| {
+                a.include(b.min_x, b.min_y);
+                a.include(b.max_x, b.max_y);
+                a
+            })
+    }
+
+    fn collect_cells(&self, id: NodeId, x: i128, y: i128, cells: &mut Vec<(i64, i64)>) {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            if let (Ok(x), Ok(y)) = (i64::try_from(x), i64::try_from(y)) {
+                cells.push((x, y));
+            }
+            return;
+        }
+        let half = 1 << (node.level - 1);
+        for ((dx, dy), child) in [(0, 0), (half, 0), (0, half), (half, half)]
+            .into_iter()
+            .zip(node.children)
+        {
+            self.collect_cells(child, x + dx, y + dy, cells);
+        }
+    }
+
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(id);
+        self.join([
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        ])
+    }
+
+    fn horizontal(&mut self, w: NodeId, e: NodeId) -> NodeId {
+        let (w, e) = (self.children(w), self.children(e));
+        self.join([w[1], e[0], w[3], e[2]])
+    }
+
+    fn vertical(&mut self, n: NodeId, s: NodeId) -> NodeId {
+        let (n, s) = (self.children(n), self.children(s));
+        self.join([n[2], n[3], s[0], s[1]])
+    }
+
+    // Central square of half the size, 2^j generations later. `None` once the cache holds
+    // more nodes than the limit.
+    fn advance(&mut self, id: NodeId, j: u8) -> Option<NodeId> {
+        let level = self.level(id);
+        if self.nodes[id as usize].population == 0 {
+            return Some(self.empty_node(level - 1));
+        }
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return Some(result);
+        }
+        if self.nodes.len() > self.limit {
+            return None;
+        }
+        let result = if level == 2 {
+            self.advance_leaf(id)
+        } else {
+            let [nw, ne, sw, se] = self.children(id);
+            let parts = [
+                nw,
+                self.horizontal(nw, ne),
+                ne,
+                self.vertical(nw, sw),
+                self.centre(id),
+                self.vertical(ne, se),
+                sw,
+                self.horizontal(sw, se),
+                se,
+            ];
+            // At full speed both halves of the time step are taken recursively, otherwise
+            // the first half only recenters the nine overlapping squares.
+            let full = j == level - 2;
+            let mut advanced = [DEAD; 9];
+            for (part, &square) in advanced.iter_mut().zip(&parts) {
+                *part = if full {
+                    self.advance(square, level - 3)?
+                } else {
+                    self.centre(square)
+                };
+            }
+            let half = if full { level - 3 } else { j };
+            let mut quadrants = [DEAD; 4];
+            for (quadrant, q) in
+                quadrants
+                    .iter_mut()
+                    .zip([[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]])
+            {
+                let square = self.join(q.map(|i| advanced[i]));
+                *quadrant = self.advance(square, half)?;
+            }
+            self.join(quadrants)
+        };
+        self.results.insert((id, j), result);
+        Some(result)
+    }
+
+    // Runs the rule on the central 2x2 cells of a 4x4 node.
+    fn advance_leaf(&mut self, id: NodeId) -> NodeId {
+        let cells: Vec<bool> = (0..16)
+            .map(|i| {
+                let (x, y) = (i % 4, i / 4);
+                let quadrant = self.children(id)[(y / 2) * 2 + x / 2];
+                self.children(quadrant)[(y % 2) * 2 + x % 2] == ALIVE
+            })
+            .collect();
+        let next = [(1, 1), (2, 1), (1, 2), (2, 2)].map(|(x, y): (isize, isize)| {
+            let neighborhood = hensel::offsets()
+                .iter()
+                .enumerate()
+                .filter(|(_, (dx, dy))| cells[((y + dy) * 4 + x + dx) as usize])
+                .fold(0, |acc, (bit, _)| acc | 1 << bit);
+            let cell = if cells[(y * 4 + x) as usize] {
+                Cell::alive()
+            } else {
+                Cell::dead()
+            };
+            if self.rule.next(cell, neighborhood).is_alive() {
+                ALIVE
+            } else {
+                DEAD
+            }
+        });
+        self.join(next)
+    }
+}
+
+impl Default for HashLife {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for HashLife {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.bounding_box() {
+            Some(bounds) => Geometry::Square.render(
+                f,
+                bounds.width() as usize,
+                bounds.height() as usize,
+                |x, y| self.get(bounds.min_x + x as i64, bounds.min_y + y as i64),
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hashlife::HashLife,
+        plane::{BoundingBox, Plane},
+        Cell,
+    };
+
+    const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    #[test]
+    fn glider_test() {
+        let mut life = HashLife::new();
+        GLIDER
+            .iter()
+            .for_each(|&(x, y)| life.set(x, y, Cell::alive()));
+        life.step(1 << 40);
+
+        let offset = 1 << 38;
+        assert_eq!(life.generation(), 1 << 40);
+        assert_eq!(life.population(), 5);
+        for (x, y) in GLIDER {
+            assert!(life.is_alive(x + offset, y + offset));
+        }
+        assert_eq!(life.to_string(), " # \n  #\n###");
+    }
+
+    #[test]
+    fn huge_step_test() {
+        let blinker = || {
+            let mut life = HashLife::new();
+            (0..3).for_each(|x| life.set(x, 0, Cell::alive()));
+            life
+        };
+        let mut life = blinker();
+        life.step(1 << 63);
+        assert_eq!(life.generation(), 1 << 63);
+        assert_eq!(life.live_cells(), vec![(0, 0), (1, 0), (2, 0)]);
+        life.step(u64::MAX);
+        assert_eq!(life.generation(), (1 << 63) + u64::MAX as u128);
+        assert_eq!(life.to_string(), "#\n#\n#");
+
+        let mut life = blinker();
+        life.step(u64::MAX);
+        assert_eq!(
+            life.bounding_box(),
+            Some(BoundingBox {
+                min_x: 1,
+                min_y: -1,
+                max_x: 1,
+                max_y: 1
+            })
+        );
+    }
+
+    #[test]
+    fn node_limit_test() {
+        let mut life = HashLife::new().with_max_nodes(1500);
+        GLIDER
+            .iter()
+            .for_each(|&(x, y)| life.set(x, y, Cell::alive()));
+        life.step(1 << 20);
+        // Together with what is left of the first step, this one wouldn't fit.
+        life.step(1 << 40);
+        assert!(life.node_count() <= 1500);
+        let offset = (1 << 18) + (1 << 38);
+        assert!(GLIDER
+            .iter()
+            .all(|&(x, y)| life.is_alive(x + offset, y + offset)));
+    }
+
+    #[test]
+    fn off_centre_test() {
+        // Cells near the edge of the root's central half must not be cut off.
+        let mut life = HashLife::new();
+        let mut plane = Plane::new();
+        for (x, y) in GLIDER {
+            life.set(x + 5, y + 5, Cell::alive());
+            plane.set(x + 5, y + 5, Cell::alive());
+        }
+        life.step(1);
+        plane.update();
+        assert_eq!(life.to_string(), plane.to_string());
+        assert_eq!(life.bounding_box(), plane.bounding_box());
+    }
+
+    #[test]
+    fn matches_plane_test() {
+        let mut life = HashLife::new()
+            .with_rule("B36/S23".parse().unwrap())
+            .unwrap()
+            .with_max_nodes(1000);
+        let mut plane = Plane::new().with_rule("B36/S23".parse().unwrap()).unwrap();
+        for i in 0..200i64 {
+            let (x, y) = ((i * 7919) % 23, (i * 104729) % 19);
+            life.set(x, y, Cell::alive());
+            plane.set(x, y, Cell::alive());
+        }
+        for n in [1, 2, 3, 10, 37] {
+            life.step(n);
+            (0..n).for_each(|_| plane.update());
+            assert_eq!(life.population(), plane.population() as u64);
+            assert_eq!(life.bounding_box(), plane.bounding_box());
+            assert_eq!(life.to_string(), plane.to_string());
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_rules_test() {
+        assert!(HashLife::new()
+            .with_rule("B2/S/C3".parse().unwrap())
+            .is_err());
+        assert!(HashLife::new()
+            .with_rule("B3/S23H".parse().unwrap())
+            .is_err());
+        assert!(HashLife::new()
+            .with_rule("B2-a/S12".parse().unwrap())
+            .is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+//! Moore neighborhood configurations and their names in Hensel notation.
+
+// Neighbors of a Moore neighborhood are numbered as bits of a configuration index:
+//
+//     1   2   4
+//     8   .  16
+//    32  64 128
+const OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+// One representative configuration for every Hensel letter of every neighbor count up to 4.
+// Counts 5..=8 use the complements of the same letters for 3..=0.
+const LETTERS: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', 1), ('e', 2)],
+    &[
+        ('c', 5),
+        ('e', 10),
+        ('k', 17),
+        ('a', 3),
+        ('i', 24),
+        ('n', 36),
+    ],
+    &[
+        ('c', 37),
+        ('e', 26),
+        ('k', 50),
+        ('a', 11),
+        ('i', 7),
+        ('n', 13),
+        ('y', 49),
+        ('q', 38),
+        ('j', 14),
+        ('r', 25),
+    ],
+    &[
+        ('c', 165),
+        ('e', 90),
+        ('k', 51),
+        ('a', 15),
+        ('i', 29),
+        ('n', 39),
+        ('y', 53),
+        ('q', 54),
+        ('j', 58),
+        ('r', 27),
+        ('t', 57),
+        ('w', 46),
+        ('z', 60),
+    ],
+];
+
+/// Offsets of the eight Moore neighbors, in the bit order of configurations.
+pub fn offsets() -> &'static [(isize, isize)] {
+    &OFFSETS
+}
+
+/// Hensel letters of the configurations with `count` live neighbors.
+pub fn letters(count: usize) -> impl Iterator<Item = char> {
+    LETTERS[count.min(8 - count)]
+        .iter()
+        .map(|(letter, _)| *letter)
+}
+
+/// Every configuration with `count` live neighbors named by `letter`, or `None` if the
+/// letter doesn't exist for that count.
+pub fn configurations(count: usize, letter: char) -> Option<impl Iterator<Item = u8>> {
+    let (_, representative) = LETTERS[count.min(8 - count)]
+        .iter()
+        .find(|(l, _)| *l == letter)?;
+    let representative = if count > 4 {
+        !representative
+    } else {
+        *representative
+    };
+    let mut orbit: Vec<u8> = symmetries()
+        .map(|transform| apply(representative, transform))
+        .collect();
+    orbit.sort_unstable();
+    orbit.dedup();
+    Some(orbit.into_iter())
+}
+
+fn symmetries() -> impl Iterator<Item = impl Fn((isize, isize)) -> (isize, isize)> {
+    (0..8).map(|i| {
+        move |(x, y): (isize, isize)| {
+            let (x, y) = (0..i % 4).fold((x, y), |(x, y), _| (-y, x));
+            if i >= 4 {
+                (-x, y)
+            } else {
+                (x, y)
+            }
+        }
+    })
+}
+
+fn apply(configuration: u8, transform: impl Fn((isize, isize)) -> (isize, isize)) -> u8 {
+    OFFSETS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| configuration & (1 << bit) != 0)
+        .map(|(_, &offset)| {
+            let target = transform(offset);
+            1 << OFFSETS.iter().position(|&o| o == target).unwrap()
+        })
+        .fold(0, |acc, bit| acc | bit)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hensel::{configurations, letters};
+
+    #[test]
+    fn letters_partition_configurations_test() {
+        for count in 0..=8 {
+            let mut all: Vec<u8> = letters(count)
+                .flat_map(|l| configurations(count, l).unwrap())
+                .collect();
+            if count == 0 || count == 8 {
+                assert!(all.is_empty());
+                continue;
+            }
+            all.sort_unstable();
+            let expected: Vec<u8> = (0..=255u8)
+                .filter(|c| c.count_ones() as usize == count)
+                .collect();
+            assert_eq!(all, expected);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Noticing when a field repeats an earlier generation.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use crate::{topology::WrapMode, GameOfLife};
+
+// Cells within this distance make up the local shape each cell is hashed with.
+const SHAPE_RADIUS: isize = 2;
+
+/// Repetition found in the history of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Generation at which the field first matched an earlier one.
+    pub generation: u64,
+    /// Generations between the two matching fields.
+    pub period: u64,
+    /// How far the field moved in one period, wrapped into the torus. Zero unless the
+    /// field is a torus and its contents travel.
+    pub displacement: (isize, isize),
+}
+
+impl Cycle {
+    /// Whether the field doesn't change at all.
+    pub fn is_still_life(&self) -> bool {
+        self.period == 1 && !self.is_translation()
+    }
+
+    /// Whether the field moves rather than staying in place.
+    pub fn is_translation(&self) -> bool {
+        self.displacement != (0, 0)
+    }
+}
+
+// Hashes of one generation. `anchor` is the cell with the smallest local shape hash if no
+// other cell shares it, and `shape` hashes the torus read from the anchor on, so it
+// doesn't change when the whole field is shifted.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    generation: u64,
+    exact: u64,
+    shape: Option<u64>,
+    anchor: Option<(usize, usize)>,
+}
+
+/// Hashes of the last generations of a field, so repeats are found without keeping copies
+/// of it. Two different fields sharing a 64-bit hash are taken to be equal.
+#[derive(Debug, Clone)]
+pub(crate) struct History {
+    capacity: usize,
+    entries: VecDeque<Entry>,
+    // Latest generation with each hash.
+    exact: HashMap<u64, u64>,
+    shapes: HashMap<u64, u64>,
+}
+
+impl History {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+            exact: HashMap::new(),
+            shapes: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.exact.clear();
+        self.shapes.clear();
+    }
+
+    /// Adds the current generation of the field, and returns how it repeats an earlier
+    /// one if it does.
+    pub(crate) fn record(&mut self, game: &GameOfLife, generation: u64) -> Option<Cycle> {
+        let entry = fingerprint(game, generation);
+        let earlier = |generation: u64| self.entries.iter().find(|e| e.generation == generation);
+        let cycle = if let Some(&first) = self.exact.get(&entry.exact) {
+            Some(Cycle {
+                generation,
+                period: generation - first,
+                displacement: (0, 0),
+            })
+        } else {
+            entry
+                .shape
+                .and_then(|shape| earlier(*self.shapes.get(&shape)?))
+                .and_then(|first| {
+                    let (old, new) = (first.anchor?, entry.anchor?);
+                    let shift = |old: usize, new: usize, size: usize| {
+                        let shift = (new + size - old) % size;
+                        if shift > size / 2 {
+                            shift as isize - size as isize
+                        } else {
+                            shift as isize
+                        }
+                    };
+                    let displacement = (
+                        shift(old.0, new.0, game.width),
+                        shift(old.1, new.1, game.height),
+                    );
+                    Some(Cycle {
+                        generation,
+                        period: generation - first.generation,
+                        displacement,
+                    })
+                })
+                // Without a shift the exact hashes would have matched.
+                .filter(Cycle::is_translation)
+        };
+
+        self.entries.push_back(entry);
+        self.exact.insert(entry.exact, generation);
+        if let Some(shape) = entry.shape {
+            self.shapes.insert(shape, generation);
+        }
+        while self.entries.len() > self.capacity {
+            let old = self.entries.pop_front().unwrap();
+            self.exact.retain(|_, &mut g| g != old.generation);
+            self.shapes.retain(|_, &mut g| g != old.generation);
+        }
+        cycle
+    }
+}
+
+pub(crate) fn mix(mut z: u64) -> u64 {
+    // SplitMix64's finalizer.
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+fn fingerprint(game: &GameOfLife, generation: u64) -> Entry {
+    let mut hasher = DefaultHasher::new();
+    game.field
+        .iter()
+        .for_each(|cell| cell.state().hash(&mut hasher));
+    let exact = hasher.finish();
+    if game.wrap != WrapMode::Wrap {
+        return Entry {
+            generation,
+            exact,
+            shape: None,
+            anchor: None,
+        };
+    }
+
+    let mut smallest: Option<(u64, usize, (usize, usize))> = None;
+    for index in (0..game.field.len()).filter(|&i| !game.field[i].is_dead()) {
+        let (x, y) = game.index_to_coords(index);
+        let local = (-SHAPE_RADIUS..=SHAPE_RADIUS)
+            .flat_map(|dy| (-SHAPE_RADIUS..=SHAPE_RADIUS).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| game.get(x as isize + dx, y as isize + dy))
+            .fold(0u64, |acc, cell| {
+                mix(acc ^ cell.map_or(0, |c| c.state()) as u64)
+            });
+        smallest = match smallest {
+            Some((hash, count, at)) if hash == local => Some((hash, count + 1, at)),
+            Some((hash, ..)) if hash < local => smallest,
+            _ => Some((local, 1, (x, y))),
+        };
+    }
+    let anchor = smallest
+        .filter(|&(_, count, _)| count == 1)
+        .map(|(.., at)| at);
+    // The local shapes alone don't say where objects are relative to each other, so the
+    // whole field is hashed, starting at the anchor.
+    let shape = anchor.map(|(ax, ay)| {
+        let mut hasher = DefaultHasher::new();
+        for y in 0..game.height {
+            for x in 0..game.width {
+                let index = game.index((ax + x) % game.width, (ay + y) % game.height);
+                game.field[index].state().hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    });
+    Entry {
+        generation,
+        exact,
+        shape,
+        anchor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{history::Cycle, topology::WrapMode, Cell, GameOfLife};
+
+    fn run(mut game: GameOfLife, generations: u64) -> Option<Cycle> {
+        for _ in 0..generations {
+            game.update();
+            if game.cycle().is_some() {
+                break;
+            }
+        }
+        game.cycle()
+    }
+
+    #[test]
+    fn oscillator_test() {
+        let block =
+            GameOfLife::from_cells(6, 6, WrapMode::NoWrap, [(1, 1), (2, 1), (1, 2), (2, 2)]);
+        let cycle = run(block.with_history(8), 10).unwrap();
+        assert_eq!((cycle.generation, cycle.period), (1, 1));
+        assert!(cycle.is_still_life());
+
+        let blinker = || GameOfLife::from_cells(5, 5, WrapMode::Wrap, [(1, 2), (2, 2), (3, 2)]);
+        let cycle = run(blinker().with_history(8), 10).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                generation: 2,
+                period: 2,
+                displacement: (0, 0)
+            }
+        );
+        // Only the previous generation is remembered.
+        assert_eq!(run(blinker().with_history(1), 10), None);
+        assert_eq!(run(blinker(), 10), None);
+    }
+
+    #[test]
+    fn translation_test() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let game = GameOfLife::from_cells(16, 12, WrapMode::Wrap, glider);
+        let cycle = run(game.with_history(16), 100).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                generation: 4,
+                period: 4,
+                displacement: (1, 1)
+            }
+        );
+        assert!(cycle.is_translation());
+
+        // The glider and the block look the same four generations later, but not in the
+        // same place relative to each other.
+        let cells = glider
+            .into_iter()
+            .chain([(30, 5), (31, 5), (30, 6), (31, 6)]);
+        let game = GameOfLife::from_cells(40, 40, WrapMode::Wrap, cells);
+        assert_eq!(run(game.with_history(16), 40), None);
+
+        // Editing the field forgets what came before.
+        let mut game =
+            GameOfLife::from_cells(8, 8, WrapMode::Wrap, [(1, 1), (2, 1), (1, 2), (2, 2)])
+                .with_history(8);
+        game.update();
+        assert!(game.cycle().is_some());
+        game.set(5, 5, Cell::alive());
+        assert_eq!(game.cycle(), None);
+    }
+}
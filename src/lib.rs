@@ -0,0 +1,732 @@
+//! Cellular automata in the Game of Life family.
+//!
+//! [`GameOfLife`] runs any [`rule::Rule`] on a bounded grid with one of the topologies of
+//! [`topology::WrapMode`]. The [`universe`] module puts it behind a common [`universe::Universe`]
+//! trait together with the other engines: [`bitpacked::BitPacked`], the unbounded
+//! [`plane::Plane`] and [`hashlife::HashLife`].
+
+#![warn(missing_docs)]
+
+pub mod apgcode;
+pub mod bitpacked;
+pub mod census;
+pub mod classify;
+pub mod geometry;
+pub mod hashlife;
+pub mod hensel;
+pub mod history;
+pub mod life;
+pub mod ltl;
+pub mod macrocell;
+pub mod neighborhood;
+pub mod pattern;
+pub mod plaintext;
+pub mod plane;
+#[cfg(feature = "parallel")]
+mod pool;
+pub mod rle;
+pub mod rule;
+pub mod search;
+pub mod soup;
+pub mod topology;
+pub mod universe;
+
+use history::{Cycle, History};
+use pattern::{Format, Pattern, PatternError};
+use rand::prelude::random;
+use rule::Rule;
+use soup::Soup;
+use std::fmt::Display;
+use topology::WrapMode;
+
+/// State of a single cell: dead, alive, or one of the dying states of rules with more
+/// than two states.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell(u8);
+
+impl Cell {
+    /// Live cell.
+    pub fn alive() -> Self {
+        Cell(1)
+    }
+
+    /// Dead cell, also the default.
+    pub fn dead() -> Self {
+        Cell(0)
+    }
+
+    /// Dying cell of the given state, counting from 2 for the first state after alive.
+    pub fn dying(state: u8) -> Self {
+        Cell(state)
+    }
+
+    /// Whether the cell is alive, which dying cells are not.
+    pub fn is_alive(&self) -> bool {
+        self.0 == 1
+    }
+
+    /// Whether the cell is dead, which dying cells are not.
+    pub fn is_dead(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// 0 when dead, 1 when alive and 2 or more when dying.
+    pub fn state(&self) -> u8 {
+        self.0
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self.0 {
+            0 => ' ',
+            1 => '#',
+            2 => '+',
+            _ => '.',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+/// Bounded field of `width * height` cells, stored row-major.
+pub struct GameOfLife {
+    width: usize,
+    height: usize,
+    field: Vec<Cell>,
+    // Next generation is written here and swapped with `field`, so stepping doesn't
+    // allocate.
+    back: Vec<Cell>,
+    // Summed-area table and neighbor counts of rules with a neighborhood, kept for the
+    // same reason.
+    table: Vec<usize>,
+    counts: Vec<usize>,
+    wrap: WrapMode,
+    rule: Rule,
+    // Tiles with a cell that changed in the last generation, and the tiles that have to be
+    // recomputed in the next one because a cell they look at changed.
+    dirty: Vec<bool>,
+    active: Vec<bool>,
+    // For every tile, the tiles holding the cells its cells look at.
+    sources: Vec<Vec<usize>>,
+    generation: u64,
+    history: Option<History>,
+    cycle: Option<Cycle>,
+    #[cfg(feature = "parallel")]
+    threads: usize,
+    // Started on the first update that needs it.
+    #[cfg(feature = "parallel")]
+    pool: std::sync::OnceLock<pool::Pool>,
+}
+
+impl GameOfLife {
+    /// Side of the square tiles the field is split into to skip stable areas.
+    pub const TILE_SIZE: usize = 16;
+
+    /// Field where every cell is alive or dead at random, different on every run.
+    pub fn new(width: usize, height: usize, wrap: WrapMode) -> Self {
+        Self::from_soup(width, height, wrap, &Soup::new(random()))
+    }
+
+    /// Field filled by a reproducible random soup.
+    pub fn from_soup(width: usize, height: usize, wrap: WrapMode, soup: &Soup) -> Self {
+        Self::from_cells(width, height, wrap, soup.cells(width, height))
+    }
+
+    /// Field with a pattern in any supported file format centered on it.
+    pub fn from_pattern_str(
+        width: usize,
+        height: usize,
+        wrap: WrapMode,
+        s: &str,
+    ) -> Result<Self, PatternError> {
+        Ok(Pattern::parse(s)?.to_game(width, height, wrap))
+    }
+
+    /// The whole field written in the given file format.
+    pub fn to_pattern_string(&self, format: Format) -> String {
+        format.write(&Pattern::from_game(self))
+    }
+
+    /// Field where every cell is dead.
+    pub fn empty(width: usize, height: usize, wrap: WrapMode) -> Self {
+        Self::from_field(width, height, wrap, vec![Cell::dead(); width * height])
+    }
+
+    /// Field where only the given cells are alive.
+    ///
+    /// # Panics
+    ///
+    /// If a cell is outside of the field.
+    pub fn from_cells(
+        width: usize,
+        height: usize,
+        wrap: WrapMode,
+        cells: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Self {
+        let mut game = Self::empty(width, height, wrap);
+        cells
+            .into_iter()
+            .for_each(|(x, y)| game.set(x, y, Cell::alive()));
+        game
+    }
+
+    fn from_field(width: usize, height: usize, wrap: WrapMode, field: Vec<Cell>) -> Self {
+        let mut game = Self {
+            width,
+            height,
+            field,
+            back: vec![Cell::dead(); width * height],
+            table: Vec::new(),
+            counts: Vec::new(),
+            wrap,
+            rule: Rule::default(),
+            dirty: Vec::new(),
+            active: Vec::new(),
+            sources: Vec::new(),
+            generation: 0,
+            history: None,
+            cycle: None,
+            #[cfg(feature = "parallel")]
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            #[cfg(feature = "parallel")]
+            pool: std::sync::OnceLock::new(),
+        };
+        game.sources = game.tile_sources();
+        game.dirty = vec![true; game.sources.len()];
+        game
+    }
+
+    /// Number of row bands `update` is split into, each stepped on a thread of a pool that
+    /// lives as long as the field. With one band no threads are started.
+    #[cfg(feature = "parallel")]
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self.pool = std::sync::OnceLock::new();
+        self
+    }
+
+    /// Runs the given rule from now on, B3/S23 by default.
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        // Tiles that were stable under the old rule may not be under the new one.
+        self.dirty.fill(true);
+        self.forget_history();
+        self
+    }
+
+    /// Remembers hashes of the last `capacity` generations to notice when the field repeats
+    /// one of them, see [`GameOfLife::cycle`].
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.history = Some(History::new(capacity));
+        self.cycle = None;
+        self
+    }
+
+    /// Number of updates so far.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// First repeat of an earlier generation since the history was enabled or the field
+    /// was last edited.
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    /// Rule the field runs.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// How the edges of the field are glued together.
+    pub fn wrap(&self) -> WrapMode {
+        self.wrap
+    }
+
+    /// Number of live cells, not counting dying ones.
+    pub fn population(&self) -> usize {
+        self.field.iter().filter(|c| c.is_alive()).count()
+    }
+
+    /// Whether every cell is dead, so nothing will ever change again unless the rule has
+    /// B0.
+    pub fn is_extinct(&self) -> bool {
+        self.field.iter().all(|c| c.is_dead())
+    }
+
+    /// Coordinates of the live cells, row by row.
+    pub fn live_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.field
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_alive())
+            .map(|(index, _)| self.index_to_coords(index))
+    }
+
+    /// Tiles whose cells changed in the last generation, as column and row of the
+    /// `TILE_SIZE` squares, so only those have to be redrawn.
+    pub fn dirty_tiles(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let columns = self.tile_columns();
+        self.dirty
+            .iter()
+            .enumerate()
+            .filter(|(_, &dirty)| dirty)
+            .map(move |(tile, _)| (tile % columns, tile / columns))
+    }
+
+    /// Advances the field by one generation, recomputing only tiles next to a change.
+    pub fn update(&mut self) {
+        if self.history.as_ref().is_some_and(History::is_empty) {
+            self.record_history();
+        }
+        // Neighborhoods other than the Moore one are counted for the whole field at once.
+        let counted = self.rule.neighborhood().is_some();
+        let mut active = std::mem::take(&mut self.active);
+        active.clear();
+        active.extend(
+            self.sources
+                .iter()
+                .map(|sources| counted || sources.iter().any(|&tile| self.dirty[tile])),
+        );
+        self.active = active;
+
+        let mut back = std::mem::take(&mut self.back);
+        back.resize(self.field.len(), Cell::dead());
+        match self.rule.neighborhood() {
+            Some(neighborhood) => {
+                let (mut table, mut counts) = (
+                    std::mem::take(&mut self.table),
+                    std::mem::take(&mut self.counts),
+                );
+                let alive = |x, y| self.is_alive(x, y);
+                neighborhood.count_into(self.width, self.height, alive, &mut table, &mut counts);
+                counts
+                    .iter()
+                    .zip(self.field.iter())
+                    .zip(back.iter_mut())
+                    .for_each(|((&count, &c), next)| *next = self.rule.next_counted(c, count));
+                (self.table, self.counts) = (table, counts);
+            }
+            None => self.update_bands(&mut back),
+        }
+        std::mem::swap(&mut self.field, &mut back);
+        self.back = back;
+
+        for tile in 0..self.dirty.len() {
+            self.dirty[tile] = self.active[tile]
+                && self
+                    .tile_cells(tile)
+                    .any(|index| self.field[index] != self.back[index]);
+        }
+        self.generation += 1;
+        self.record_history();
+    }
+
+    fn record_history(&mut self) {
+        if let Some(mut history) = self.history.take() {
+            let cycle = history.record(self, self.generation);
+            self.cycle = self.cycle.or(cycle);
+            self.history = Some(history);
+        }
+    }
+
+    fn forget_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.cycle = None;
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn update_bands(&self, back: &mut [Cell]) {
+        self.update_rows(0, back);
+    }
+
+    // Every band only writes its own rows of `back` and reads the shared field, so rows
+    // wrapping across band boundaries see the same generation as in a serial update.
+    #[cfg(feature = "parallel")]
+    fn update_bands(&self, back: &mut [Cell]) {
+        if self.threads <= 1 {
+            return self.update_rows(0, back);
+        }
+        let rows = self.height.div_ceil(self.threads).max(1);
+        let pool = self.pool.get_or_init(|| pool::Pool::new(self.threads));
+        pool.run(back.chunks_mut((rows * self.width).max(1)).enumerate().map(
+            |(band, rows_back)| -> Box<dyn FnOnce() + Send + '_> {
+                Box::new(move || self.update_rows(band * rows, rows_back))
+            },
+        ));
+    }
+
+    // Writes the next generation of the rows starting at `first` into `back`.
+    fn update_rows(&self, first: usize, back: &mut [Cell]) {
+        for (i, next) in back.iter_mut().enumerate() {
+            let (x, y) = (i % self.width, first + i / self.width);
+            let index = self.index(x, y);
+            if !self.active[self.tile(x, y)] {
+                *next = self.field[index];
+                continue;
+            }
+            let border = x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height;
+            let neighborhood = if border {
+                self.neighborhood(index)
+            } else {
+                self.interior_neighborhood(index)
+            };
+            *next = self.rule.next(self.field[index], neighborhood);
+        }
+    }
+
+    /// Cell at the given coordinates, mapped through the topology if they are outside of
+    /// the field. `None` if they don't map onto it.
+    pub fn get(&self, x: isize, y: isize) -> Option<&Cell> {
+        let (x, y) = self.wrap.wrap(x, y, self.width, self.height)?;
+        let index = self.index(x, y);
+        self.field.get(index)
+    }
+
+    /// Whether the cell at the coordinates, mapped like in [`GameOfLife::get`], is alive.
+    pub fn is_alive(&self, x: isize, y: isize) -> bool {
+        self.get(x, y).map(|c| c.is_alive()).unwrap_or(false)
+    }
+
+    /// # Panics
+    ///
+    /// If the cell is outside of the field.
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        let index = self.index(x, y);
+        if self.field[index] != cell {
+            self.field[index] = cell;
+            let tile = self.tile(x, y);
+            self.dirty[tile] = true;
+            self.forget_history();
+        }
+    }
+
+    /// Makes a dead cell alive and any other cell dead.
+    ///
+    /// # Panics
+    ///
+    /// If the cell is outside of the field.
+    pub fn toggle(&mut self, x: usize, y: usize) {
+        let cell = if self.field[self.index(x, y)].is_dead() {
+            Cell::alive()
+        } else {
+            Cell::dead()
+        };
+        self.set(x, y, cell);
+    }
+
+    /// Number of live neighbors of every cell under the rule's neighborhood, row by row.
+    pub fn neighbor_counts(&self) -> Vec<usize> {
+        match self.rule.neighborhood() {
+            Some(neighborhood) => {
+                neighborhood.count(self.width, self.height, |x, y| self.is_alive(x, y))
+            }
+            None => (0..self.field.len())
+                .map(|i| self.count_neighbors(i))
+                .collect(),
+        }
+    }
+
+    fn tile_columns(&self) -> usize {
+        self.width.div_ceil(Self::TILE_SIZE)
+    }
+
+    fn tile(&self, x: usize, y: usize) -> usize {
+        (y / Self::TILE_SIZE) * self.tile_columns() + x / Self::TILE_SIZE
+    }
+
+    fn tile_cells(&self, tile: usize) -> impl Iterator<Item = usize> + '_ {
+        let columns = self.tile_columns();
+        let (x, y) = (
+            tile % columns * Self::TILE_SIZE,
+            tile / columns * Self::TILE_SIZE,
+        );
+        let xs = x..(x + Self::TILE_SIZE).min(self.width);
+        (y..(y + Self::TILE_SIZE).min(self.height))
+            .flat_map(move |y| xs.clone().map(move |x| self.index(x, y)))
+    }
+
+    // The cells of a tile look at the tile itself and at the ring of cells around it, which
+    // may wrap anywhere depending on the topology.
+    fn tile_sources(&self) -> Vec<Vec<usize>> {
+        let columns = self.tile_columns();
+        let tiles = columns * self.height.div_ceil(Self::TILE_SIZE);
+        let size = Self::TILE_SIZE as isize;
+        (0..tiles)
+            .map(|tile| {
+                let x0 = (tile % columns) as isize * size;
+                let y0 = (tile / columns) as isize * size;
+                let mut sources: Vec<usize> = (y0 - 1..=y0 + size)
+                    .flat_map(|y| (x0 - 1..=x0 + size).map(move |x| (x, y)))
+                    .filter(|&(x, y)| {
+                        x < x0 || y < y0 || x == x0 + size || y == y0 + size || (x, y) == (x0, y0)
+                    })
+                    .filter_map(|(x, y)| self.wrap.wrap(x, y, self.width, self.height))
+                    .map(|(x, y)| self.tile(x, y))
+                    .chain([tile])
+                    .collect();
+                sources.sort_unstable();
+                sources.dedup();
+                sources
+            })
+            .collect()
+    }
+
+    fn count_neighbors(&self, index: usize) -> usize {
+        self.neighborhood(index).count_ones() as usize
+    }
+
+    fn neighborhood(&self, index: usize) -> u8 {
+        let (x, y) = self.index_to_coords(index);
+        let x = x as isize;
+        let y = y as isize;
+
+        hensel::offsets()
+            .iter()
+            .enumerate()
+            .filter(|(_, (i, j))| self.is_alive(x + i, y + j))
+            .fold(0, |acc, (bit, _)| acc | 1 << bit)
+    }
+
+    // Same as `neighborhood` for cells away from the edges, which never need wrapping.
+    fn interior_neighborhood(&self, index: usize) -> u8 {
+        let width = self.width as isize;
+        hensel::offsets()
+            .iter()
+            .enumerate()
+            .filter(|(_, (i, j))| self.field[(index as isize + j * width + i) as usize].is_alive())
+            .fold(0, |acc, (bit, _)| acc | 1 << bit)
+    }
+
+    fn index_to_coords(&self, index: usize) -> (usize, usize) {
+        let x = index % self.width;
+        let y = index / self.width;
+
+        (x, y)
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+}
+
+impl Display for GameOfLife {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.rule
+            .geometry()
+            .render(f, self.width, self.height, |x, y| {
+                self.field[self.index(x, y)]
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pattern::Format, rule::Rule, topology::Axis, Cell, GameOfLife, WrapMode};
+
+    #[test]
+    fn index_to_coords_test() {
+        let game = GameOfLife::new(10, 5, WrapMode::NoWrap);
+
+        assert_eq!(game.index_to_coords(0), (0, 0));
+        assert_eq!(game.index_to_coords(1), (1, 0));
+        assert_eq!(game.index_to_coords(5), (5, 0));
+        assert_eq!(game.index_to_coords(10), (0, 1));
+        assert_eq!(game.index_to_coords(49), (9, 4));
+    }
+
+    #[test]
+    fn index_test() {
+        let game = GameOfLife::new(10, 5, WrapMode::Wrap);
+        assert_eq!(game.index(0, 0), 0);
+        assert_eq!(game.index(1, 0), 1);
+        assert_eq!(game.index(5, 0), 5);
+        assert_eq!(game.index(0, 1), 10);
+        assert_eq!(game.index(9, 4), 49);
+    }
+
+    #[test]
+    fn rule_update_test() {
+        let mut game = GameOfLife::new(5, 5, WrapMode::NoWrap).with_rule("B2/S".parse().unwrap());
+        game.field = vec![Cell::dead(); 25];
+        game.field[7] = Cell::alive();
+        game.field[12] = Cell::alive();
+        game.update();
+
+        let alive: Vec<_> = (0..25).filter(|&i| game.field[i].is_alive()).collect();
+        assert_eq!(
+            alive,
+            vec![
+                game.index(1, 1),
+                game.index(3, 1),
+                game.index(1, 2),
+                game.index(3, 2)
+            ]
+        );
+        assert_eq!(game.rule(), &Rule::preset("seeds").unwrap());
+    }
+
+    #[test]
+    fn ltl_matches_life_test() {
+        let mut life = GameOfLife::new(12, 9, WrapMode::Wrap);
+        let mut ltl = GameOfLife::new(12, 9, WrapMode::Wrap)
+            .with_rule("R1,C0,M0,S2..3,B3..3,NM".parse().unwrap());
+        ltl.field = life.field.clone();
+        for _ in 0..10 {
+            life.update();
+            ltl.update();
+            assert_eq!(life.field, ltl.field);
+        }
+    }
+
+    #[test]
+    fn hexagonal_update_test() {
+        let mut game =
+            GameOfLife::new(4, 4, WrapMode::NoWrap).with_rule("B2/S34H".parse().unwrap());
+        game.field = vec![Cell::dead(); 16];
+        game.field[5] = Cell::alive();
+        game.field[6] = Cell::alive();
+        game.update();
+
+        // (1, 1) and (2, 1) share the hexagonal neighbors (1, 0) and (2, 2). (2, 0) and
+        // (1, 2) are Moore neighbors of both, but not hexagonal ones.
+        let alive: Vec<_> = (0..16).filter(|&i| game.field[i].is_alive()).collect();
+        assert_eq!(alive, vec![game.index(1, 0), game.index(2, 2)]);
+    }
+
+    #[test]
+    fn klein_bottle_neighbors_test() {
+        let mut game = GameOfLife::new(4, 3, WrapMode::Klein(Axis::Horizontal));
+        game.field = vec![Cell::dead(); 12];
+        game.field[8] = Cell::alive();
+
+        // Crossing the top edge of (3, 0) twists back to the bottom-left corner.
+        assert!(game.is_alive(3, -1));
+        assert!(!game.is_alive(0, -1));
+        assert_eq!(game.count_neighbors(game.index(3, 0)), 1);
+    }
+
+    #[test]
+    fn double_buffer_update_test() {
+        for wrap in [
+            WrapMode::Wrap,
+            WrapMode::NoWrap,
+            WrapMode::Klein(Axis::Vertical),
+            WrapMode::ShiftedTorus(Axis::Horizontal, 3),
+            WrapMode::Sphere,
+        ] {
+            let mut game = GameOfLife::new(37, 37, wrap);
+            for _ in 0..20 {
+                let expected: Vec<_> = (0..game.field.len())
+                    .map(|i| game.rule.next(game.field[i], game.neighborhood(i)))
+                    .collect();
+                let back = game.back.as_ptr();
+                game.update();
+                assert_eq!(game.field, expected);
+                assert_eq!(game.field.as_ptr(), back);
+            }
+        }
+
+        // Rules with a neighborhood reuse their counting buffers too.
+        let mut game = GameOfLife::new(37, 37, WrapMode::Wrap)
+            .with_rule("R2,C0,M0,S5..8,B6..7,NM".parse().unwrap());
+        game.update();
+        let buffers = (game.table.as_ptr(), game.counts.as_ptr());
+        for _ in 0..5 {
+            let expected: Vec<_> = game
+                .neighbor_counts()
+                .into_iter()
+                .zip(&game.field)
+                .map(|(count, &c)| game.rule.next_counted(c, count))
+                .collect();
+            game.update();
+            assert_eq!(game.field, expected);
+            assert_eq!((game.table.as_ptr(), game.counts.as_ptr()), buffers);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_update_test() {
+        for (wrap, threads) in [
+            (WrapMode::Wrap, 4),
+            (WrapMode::NoWrap, 3),
+            (WrapMode::Klein(Axis::Horizontal), 7),
+            (WrapMode::Wrap, 64),
+        ] {
+            let mut game = GameOfLife::new(31, 23, wrap).with_threads(threads);
+            for _ in 0..10 {
+                let expected: Vec<_> = (0..game.field.len())
+                    .map(|i| game.rule.next(game.field[i], game.neighborhood(i)))
+                    .collect();
+                game.update();
+                assert_eq!(game.field, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn dirty_tiles_test() {
+        let mut game = GameOfLife::new(64, 48, WrapMode::Wrap);
+        game.field = vec![Cell::dead(); 64 * 48];
+        // A blinker across the top edge and a block that never changes.
+        for index in [game.index(20, 47), game.index(20, 0), game.index(20, 1)] {
+            game.field[index] = Cell::alive();
+        }
+        for index in [40, 41]
+            .map(|x| [30, 31].map(|y| game.index(x, y)))
+            .concat()
+        {
+            game.field[index] = Cell::alive();
+        }
+
+        game.update();
+        assert_eq!(game.dirty_tiles().collect::<Vec<_>>(), vec![(1, 0), (1, 2)]);
+        assert_eq!(game.active.iter().filter(|&&active| active).count(), 12);
+        game.update();
+        // Only the column of tiles around the blinker, since the grid wraps vertically.
+        assert_eq!(game.active.iter().filter(|&&active| active).count(), 9);
+        assert!(game.is_alive(20, 47) && game.is_alive(41, 31));
+    }
+
+    #[test]
+    fn public_api_test() {
+        let mut game = GameOfLife::from_cells(5, 4, WrapMode::NoWrap, [(1, 2), (2, 2), (3, 2)]);
+        assert_eq!((game.width(), game.height()), (5, 4));
+        assert_eq!(game.population(), 3);
+        game.toggle(3, 2);
+        game.toggle(4, 0);
+        assert_eq!(
+            game.live_cells().collect::<Vec<_>>(),
+            vec![(4, 0), (1, 2), (2, 2)]
+        );
+        assert_eq!(
+            game.neighbor_counts(),
+            [0, 0, 0, 1, 0, 1, 2, 2, 2, 1, 1, 1, 1, 1, 0, 1, 2, 2, 1, 0]
+        );
+        game.update();
+        assert!(game.is_extinct());
+    }
+
+    #[test]
+    fn pattern_string_test() {
+        let blinker = "#Life 1.06\n0 0\n1 0\n2 0\n";
+        let mut game = GameOfLife::from_pattern_str(5, 3, WrapMode::NoWrap, blinker).unwrap();
+        assert_eq!(game.to_pattern_string(Format::Plaintext), "\n.OOO\n\n");
+        game.update();
+        assert_eq!(game.to_pattern_string(Format::Plaintext), "..O\n..O\n..O\n");
+        assert!(GameOfLife::from_pattern_str(5, 3, WrapMode::NoWrap, "x = 1\nq!").is_err());
+    }
+}
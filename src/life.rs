@@ -0,0 +1,331 @@
+//! Life 1.05 and Life 1.06 pattern files.
+
+use std::fmt::Display;
+
+use crate::{
+    pattern::Pattern,
+    rule::{Rule, RuleError},
+    Cell,
+};
+
+/// Error reading a Life 1.05 or 1.06 file. Lines and columns count from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LifeError {
+    /// The first line isn't `#Life 1.05` or `#Life 1.06`.
+    MissingHeader,
+    /// The `#R` line doesn't hold a valid rule.
+    InvalidRule(usize, RuleError),
+    /// Line that is neither a comment nor a valid row or pair of coordinates.
+    InvalidLine(usize, String),
+    /// Character other than `.`, `*` and `O` in a row of cells.
+    UnexpectedChar(usize, usize, char),
+    /// Cell beyond the range of `i64` coordinates.
+    OutOfBounds(usize, usize),
+    /// The cells span more than `MAX_SIZE` columns or rows.
+    TooLarge,
+}
+
+impl Display for LifeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LifeError::MissingHeader => write!(f, "missing '#Life 1.0x' header line"),
+            LifeError::InvalidRule(line, e) => write!(f, "line {}: {}", line, e),
+            LifeError::InvalidLine(line, s) => write!(f, "line {}: invalid line '{}'", line, s),
+            LifeError::UnexpectedChar(line, column, c) => write!(
+                f,
+                "line {}, column {}: unexpected character '{}'",
+                line, column, c
+            ),
+            LifeError::OutOfBounds(line, column) => write!(
+                f,
+                "line {}, column {}: cell out of the range of coordinates",
+                line, column
+            ),
+            LifeError::TooLarge => write!(f, "pattern is wider or taller than {} cells", MAX_SIZE),
+        }
+    }
+}
+
+impl std::error::Error for LifeError {}
+
+const HEADER_105: &str = "#Life 1.05";
+const HEADER_106: &str = "#Life 1.06";
+// Patterns are laid out as a grid when written, so sizes beyond this are refused.
+const MAX_SIZE: u64 = 1 << 20;
+
+fn coordinates(number: usize, line: &str) -> Result<(i64, i64), LifeError> {
+    let invalid = || LifeError::InvalidLine(number, line.to_string());
+    let coordinates: Vec<i64> = line
+        .split_whitespace()
+        .map(|n| n.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    match coordinates[..] {
+        [x, y] => Ok((x, y)),
+        _ => Err(invalid()),
+    }
+}
+
+// Fills in the size, origin and cells of a pattern from cells on the plane.
+fn place(pattern: &mut Pattern, cells: &[(i64, i64)]) -> Result<(), LifeError> {
+    let Some(&(x, y)) = cells.first() else {
+        return Ok(());
+    };
+    let (mut left, mut top, mut right, mut bottom) = (x, y, x, y);
+    for &(x, y) in cells {
+        (left, right) = (left.min(x), right.max(x));
+        (top, bottom) = (top.min(y), bottom.max(y));
+    }
+    let size = |min: i64, max: i64| {
+        let size = max.abs_diff(min).checked_add(1)?;
+        (size <= MAX_SIZE).then_some(size as usize)
+    };
+    pattern.width = size(left, right).ok_or(LifeError::TooLarge)?;
+    pattern.height = size(top, bottom).ok_or(LifeError::TooLarge)?;
+    pattern.origin = (left, top);
+    pattern.cells = cells
+        .iter()
+        .map(|&(x, y)| {
+            let position = (x.abs_diff(left) as usize, y.abs_diff(top) as usize);
+            (position, Cell::alive())
+        })
+        .collect();
+    pattern.cells.sort_by_key(|&((x, y), _)| (y, x));
+    pattern.cells.dedup();
+    Ok(())
+}
+
+// Live cells on the plane, in row order.
+fn plane_cells(pattern: &Pattern) -> Vec<(i64, i64)> {
+    let (left, top) = pattern.origin;
+    let mut cells: Vec<_> = pattern
+        .cells
+        .iter()
+        .filter(|(_, cell)| cell.is_alive())
+        .map(|&((x, y), _)| (left + x as i64, top + y as i64))
+        .collect();
+    cells.sort_by_key(|&(x, y)| (y, x));
+    cells
+}
+
+/// Reads Life 1.05: `#D` description lines, an optional `#N` or `#R` rule and `#P` blocks
+/// of `.` and `*` rows, each placed at its own coordinates.
+pub fn parse_105(s: &str) -> Result<Pattern, LifeError> {
+    let mut lines = s
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+        // Blank lines before the header are skipped, as in `Format::detect`.
+        .skip_while(|(_, line)| line.trim().is_empty());
+    if !lines
+        .next()
+        .is_some_and(|(_, line)| line.trim_start().starts_with(HEADER_105))
+    {
+        return Err(LifeError::MissingHeader);
+    }
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+    let mut block: Option<(i64, i64)> = None;
+    let mut y = 0;
+    for (number, line) in lines {
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let text = chars.as_str().trim();
+            match kind {
+                Some('D' | 'C') => pattern.comments.push(text.to_string()),
+                Some('N') => pattern.rule = Some(Rule::conway()),
+                Some('R') => {
+                    let rule = text
+                        .parse()
+                        .map_err(|e| LifeError::InvalidRule(number, e))?;
+                    pattern.rule = Some(rule);
+                }
+                Some('P') => {
+                    block = Some(coordinates(number, text)?);
+                    y = 0;
+                }
+                _ => return Err(LifeError::InvalidLine(number, line.to_string())),
+            }
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((left, top)) = block else {
+            return Err(LifeError::InvalidLine(number, line.to_string()));
+        };
+        for (column, c) in line.char_indices().map(|(i, c)| (i + 1, c)) {
+            match c {
+                '.' => {}
+                '*' | 'O' => {
+                    let x = left.checked_add(column as i64 - 1);
+                    let cell = x.zip(top.checked_add(y));
+                    cells.push(cell.ok_or(LifeError::OutOfBounds(number, column))?);
+                }
+                c => return Err(LifeError::UnexpectedChar(number, column, c)),
+            }
+        }
+        y += 1;
+    }
+    place(&mut pattern, &cells)?;
+    Ok(pattern)
+}
+
+// Life 1.05 gives rules as survival/birth. Anything beyond plain outer totalistic rules is
+// written the way the rest of this crate writes it.
+fn survival_birth(rule: &Rule) -> String {
+    let rule = rule.to_string();
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    match rule
+        .strip_prefix('B')
+        .and_then(|rest| rest.split_once("/S"))
+    {
+        Some((birth, survival)) if digits(birth) && digits(survival) => {
+            format!("{}/{}", survival, birth)
+        }
+        _ => rule,
+    }
+}
+
+/// Writes the pattern as a single `#P` block at its origin, with the name and author as
+/// the first description lines.
+pub fn write_105(pattern: &Pattern) -> String {
+    let mut out = format!("{}\n", HEADER_105);
+    let description = pattern
+        .name
+        .iter()
+        .chain(&pattern.author)
+        .chain(&pattern.comments);
+    for line in description {
+        out.push_str(&format!("#D {}\n", line));
+    }
+    match pattern.rule {
+        None => {}
+        Some(rule) if rule == Rule::conway() => out.push_str("#N\n"),
+        Some(rule) => out.push_str(&format!("#R {}\n", survival_birth(&rule))),
+    }
+    out.push_str(&format!("#P {} {}\n", pattern.origin.0, pattern.origin.1));
+    for row in pattern.rows() {
+        let end = row.iter().rposition(|c| c.is_alive()).map_or(0, |i| i + 1);
+        out.extend(
+            row[..end]
+                .iter()
+                .map(|c| if c.is_alive() { '*' } else { '.' }),
+        );
+        // Some readers skip empty lines, so empty rows keep a dot.
+        if end == 0 {
+            out.push('.');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Reads Life 1.06: the header followed by one `x y` pair per live cell.
+pub fn parse_106(s: &str) -> Result<Pattern, LifeError> {
+    let mut lines = s
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .skip_while(|(_, line)| line.is_empty());
+    if !lines
+        .next()
+        .is_some_and(|(_, line)| line.starts_with(HEADER_106))
+    {
+        return Err(LifeError::MissingHeader);
+    }
+    let mut pattern = Pattern::default();
+    let cells = lines
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| coordinates(number, line))
+        .collect::<Result<Vec<_>, _>>()?;
+    place(&mut pattern, &cells)?;
+    Ok(pattern)
+}
+
+/// Writes the live cells as coordinates on the plane. The format has no room for
+/// metadata, so only the cells are kept.
+pub fn write_106(pattern: &Pattern) -> String {
+    let mut out = format!("{}\n", HEADER_106);
+    for (x, y) in plane_cells(pattern) {
+        out.push_str(&format!("{} {}\n", x, y));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        life::{parse_105, parse_106, write_105, write_106, LifeError},
+        rule::Rule,
+        Cell,
+    };
+
+    #[test]
+    fn life_105_test() {
+        let glider = "#Life 1.05\n#D Glider\n#N\n#P -1 -1\n.*\n..*\n***\n";
+        let pattern = parse_105(glider).unwrap();
+        assert_eq!(pattern.comments, vec!["Glider"]);
+        assert_eq!(pattern.rule, Some(Rule::conway()));
+        assert_eq!(pattern.origin, (-1, -1));
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.cells[0], ((1, 0), Cell::alive()));
+        assert_eq!(write_105(&pattern), glider);
+        assert_eq!(parse_105(&format!("\n  \n{}", glider)), Ok(pattern));
+
+        // Blocks are placed independently and merged into one.
+        let blocks = "#Life 1.05\n#R 23/36\n#P 0 0\n**\n#P 3 2\n*\n";
+        let pattern = parse_105(blocks).unwrap();
+        assert_eq!(pattern.rule, Some("B36/S23".parse().unwrap()));
+        assert_eq!((pattern.width, pattern.height), (4, 3));
+        assert_eq!(
+            write_105(&pattern),
+            "#Life 1.05\n#R 23/36\n#P 0 0\n**\n.\n...*\n"
+        );
+    }
+
+    #[test]
+    fn life_106_test() {
+        let glider = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        let pattern = parse_106(glider).unwrap();
+        assert_eq!(pattern.origin, (-1, -1));
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.cells.len(), 5);
+        assert_eq!(write_106(&pattern), glider);
+        assert_eq!(parse_106(&format!("\n\n{}", glider)), Ok(pattern));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(parse_106("0 0\n"), Err(LifeError::MissingHeader));
+        assert_eq!(
+            parse_106("#Life 1.06\n0 0\n1\n"),
+            Err(LifeError::InvalidLine(3, "1".to_string()))
+        );
+        assert_eq!(
+            parse_105("#Life 1.05\n*\n"),
+            Err(LifeError::InvalidLine(2, "*".to_string()))
+        );
+        assert_eq!(
+            parse_105("#Life 1.05\n#P 0 0\n.*o\n"),
+            Err(LifeError::UnexpectedChar(3, 3, 'o'))
+        );
+
+        // Extreme coordinates are fine as long as the pattern stays small.
+        let corner = parse_106("#Life 1.06\n-9223372036854775808 9223372036854775807\n").unwrap();
+        assert_eq!((corner.width, corner.height), (1, 1));
+        assert_eq!(
+            parse_106("#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0\n"),
+            Err(LifeError::TooLarge)
+        );
+        assert_eq!(
+            parse_105("#Life 1.05\n#P -9223372036854775808 0\n*\n#P 0 0\n*\n"),
+            Err(LifeError::TooLarge)
+        );
+        assert_eq!(
+            parse_105("#Life 1.05\n#P 9223372036854775807 0\n.*\n"),
+            Err(LifeError::OutOfBounds(3, 2))
+        );
+    }
+}
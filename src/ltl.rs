@@ -0,0 +1,144 @@
+//! Larger than Life rules in Golly's notation.
+
+use std::fmt::Display;
+
+use crate::{
+    neighborhood::{Neighborhood, Shape},
+    rule::RuleError,
+};
+
+const MAX_RADIUS: usize = 500;
+
+/// Larger than Life rule in Golly's `R1,C0,M0,S2..3,B3..3,NM` notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ltl {
+    neighborhood: Neighborhood,
+    survival: (usize, usize),
+    birth: (usize, usize),
+}
+
+impl Ltl {
+    /// Whether the string looks like a Larger than Life rule, starting with `R` and a
+    /// digit.
+    pub fn is_ltl(s: &str) -> bool {
+        let mut chars = s.chars();
+        matches!(chars.next(), Some('R' | 'r')) && chars.next().is_some_and(|c| c.is_ascii_digit())
+    }
+
+    /// Returns the rule together with its number of states (`C`).
+    pub fn parse(s: &str) -> Result<(Self, u8), RuleError> {
+        let mut radius = None;
+        let mut states = None;
+        let mut middle = None;
+        let mut survival = None;
+        let mut birth = None;
+        let mut shape = None;
+        for token in s.split(',') {
+            let token = token.trim();
+            let first = token.chars().next().ok_or(RuleError::UnexpectedChar(','))?;
+            let name = first.to_ascii_uppercase();
+            let value = &token[first.len_utf8()..];
+            let invalid = || RuleError::InvalidValue(name, value.to_string());
+            let duplicate = match name {
+                'R' => radius
+                    .replace(
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|r| (1..=MAX_RADIUS).contains(r))
+                            .ok_or_else(invalid)?,
+                    )
+                    .is_some(),
+                'C' => states
+                    .replace(match value.parse::<u8>() {
+                        Ok(0) => 2,
+                        Ok(c) if c >= 2 => c,
+                        _ => return Err(invalid()),
+                    })
+                    .is_some(),
+                'M' => middle
+                    .replace(match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(invalid()),
+                    })
+                    .is_some(),
+                'S' => survival
+                    .replace(Self::parse_range(value).ok_or_else(invalid)?)
+                    .is_some(),
+                'B' => birth
+                    .replace(Self::parse_range(value).ok_or_else(invalid)?)
+                    .is_some(),
+                'N' => shape
+                    .replace(match value.to_ascii_uppercase().as_str() {
+                        "M" => Shape::Moore,
+                        "N" => Shape::VonNeumann,
+                        "C" => Shape::Circular,
+                        _ => return Err(invalid()),
+                    })
+                    .is_some(),
+                c => return Err(RuleError::UnexpectedChar(c)),
+            };
+            if duplicate {
+                return Err(RuleError::DuplicateSection(name));
+            }
+        }
+
+        let neighborhood = Neighborhood::new(
+            shape.unwrap_or(Shape::Moore),
+            radius.ok_or(RuleError::MissingSection('R'))?,
+            middle.unwrap_or(false),
+        );
+        let survival = survival.ok_or(RuleError::MissingSection('S'))?;
+        let birth = birth.ok_or(RuleError::MissingSection('B'))?;
+        for (_, max) in [survival, birth] {
+            if max > neighborhood.size() {
+                return Err(RuleError::NeighborCount(max));
+            }
+        }
+        Ok((
+            Self {
+                neighborhood,
+                survival,
+                birth,
+            },
+            states.unwrap_or(2),
+        ))
+    }
+
+    /// Neighborhood whose live cells are counted.
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// Whether a dead cell with `count` live neighbors comes alive.
+    pub fn born(&self, count: usize) -> bool {
+        (self.birth.0..=self.birth.1).contains(&count)
+    }
+
+    /// Whether a live cell with `count` live neighbors stays alive.
+    pub fn survives(&self, count: usize) -> bool {
+        (self.survival.0..=self.survival.1).contains(&count)
+    }
+
+    fn parse_range(s: &str) -> Option<(usize, usize)> {
+        let (min, max) = s.split_once("..")?;
+        let (min, max) = (min.parse().ok()?, max.parse().ok()?);
+        (min <= max).then_some((min, max))
+    }
+}
+
+impl Display for Ltl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "M{},S{}..{},B{}..{},N{}",
+            u8::from(self.neighborhood.middle()),
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1,
+            self.neighborhood.shape()
+        )
+    }
+}
@@ -0,0 +1,614 @@
+//! Golly's macrocell pattern files.
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    hashlife::HashLife,
+    pattern::Pattern,
+    plane::BoundingBox,
+    rule::{Rule, RuleError},
+    topology::Topology,
+    universe::{Universe, UniverseError},
+    Cell,
+};
+
+const HEADER: &str = "[M2]";
+// Deeper roots would put cells outside of the `i64` plane.
+const MAX_LEVEL: u8 = 62;
+
+/// Error reading a macrocell file. Lines and columns count from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MacrocellError {
+    /// The first line isn't the `[M2]` header.
+    MissingHeader,
+    /// The `#R` line doesn't hold a valid rule.
+    InvalidRule(usize, RuleError),
+    /// Line that is neither a comment nor a node.
+    InvalidLine(usize, String),
+    /// Character other than `.`, `*` and `$` in a leaf node.
+    UnexpectedChar(usize, usize, char),
+    /// Node referring to a node that doesn't come before it or has the wrong level.
+    InvalidReference(usize, usize),
+    /// State the rule doesn't have.
+    InvalidState(usize, u8),
+}
+
+impl Display for MacrocellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MacrocellError::MissingHeader => write!(f, "missing '{}' header line", HEADER),
+            MacrocellError::InvalidRule(line, e) => write!(f, "line {}: {}", line, e),
+            MacrocellError::InvalidLine(line, s) => {
+                write!(f, "line {}: invalid line '{}'", line, s)
+            }
+            MacrocellError::UnexpectedChar(line, column, c) => write!(
+                f,
+                "line {}, column {}: unexpected character '{}'",
+                line, column, c
+            ),
+            MacrocellError::InvalidReference(line, node) => write!(
+                f,
+                "line {}: node {} is not defined yet or has the wrong size",
+                line, node
+            ),
+            MacrocellError::InvalidState(line, state) => {
+                write!(
+                    f,
+                    "line {}: state {} is not valid for the rule",
+                    line, state
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MacrocellError {}
+
+// Node of the quadtree as it appears in the file. Nodes refer to the ones before them by
+// their position counting from 1, 0 stands for an empty square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum MacroNode {
+    // 8x8 two-state leaf, one byte per row with bit x set for a live cell in column x.
+    Bits([u8; 8]),
+    // 2x2 multistate leaf in `nw, ne, sw, se` order.
+    States([u8; 4]),
+    // Level and quadrants in `nw, ne, sw, se` order.
+    Inner(u8, [usize; 4]),
+}
+
+impl MacroNode {
+    pub(crate) fn level(&self) -> u8 {
+        match self {
+            MacroNode::Bits(_) => 3,
+            MacroNode::States(_) => 1,
+            MacroNode::Inner(level, _) => *level,
+        }
+    }
+}
+
+/// Golly's macrocell format: a quadtree of 2^level cells whose identical squares are
+/// written once, so huge regular patterns stay small. The root is centered on the origin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Macrocell {
+    /// Name from the `#N` line.
+    pub name: Option<String>,
+    /// Author from the `#O` line.
+    pub author: Option<String>,
+    /// Lines of `#C` and `#D` comments.
+    pub comments: Vec<String>,
+    /// Rule from the `#R` line.
+    pub rule: Option<Rule>,
+    /// Generation from the `#G` line.
+    pub generation: u128,
+    // The last node is the root.
+    pub(crate) nodes: Vec<MacroNode>,
+}
+
+impl Macrocell {
+    /// Level of the root, which covers 2^level by 2^level cells.
+    pub fn level(&self) -> u8 {
+        self.nodes.last().map_or(0, MacroNode::level)
+    }
+
+    /// Smallest box containing every cell that isn't dead.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        // Boxes relative to the top left corner of each node, in file order so children
+        // come before their parents.
+        let mut boxes: Vec<Option<BoundingBox>> = vec![None];
+        for node in &self.nodes {
+            let bounds = match *node {
+                MacroNode::Bits(rows) => cells_of_bits(rows).fold(None, include),
+                MacroNode::States(states) => (0..4)
+                    .filter(|&i| states[i] != 0)
+                    .map(|i| ((i % 2) as i64, (i / 2) as i64))
+                    .fold(None, include),
+                MacroNode::Inner(level, children) => {
+                    let half = 1i64 << (level - 1);
+                    quadrant_offsets(half)
+                        .into_iter()
+                        .zip(children)
+                        .filter_map(|((dx, dy), child)| Some((dx, dy, boxes[child]?)))
+                        .flat_map(|(dx, dy, b)| {
+                            [(b.min_x + dx, b.min_y + dy), (b.max_x + dx, b.max_y + dy)]
+                        })
+                        .fold(None, include)
+                }
+            };
+            boxes.push(bounds);
+        }
+        let mut bounds = boxes.pop().flatten()?;
+        let corner = self.corner();
+        bounds.min_x += corner;
+        bounds.min_y += corner;
+        bounds.max_x += corner;
+        bounds.max_y += corner;
+        Some(bounds)
+    }
+
+    /// Every cell that isn't dead, placed at the top left corner of its bounding box.
+    pub fn to_pattern(&self) -> Pattern {
+        let mut pattern = Pattern {
+            name: self.name.clone(),
+            author: self.author.clone(),
+            comments: self.comments.clone(),
+            rule: self.rule,
+            ..Pattern::default()
+        };
+        let Some(bounds) = self.bounding_box() else {
+            return pattern;
+        };
+        let mut cells = Vec::new();
+        let corner = self.corner();
+        self.collect_cells(self.nodes.len(), corner, corner, &mut cells);
+        pattern.origin = (bounds.min_x, bounds.min_y);
+        pattern.width = bounds.width() as usize;
+        pattern.height = bounds.height() as usize;
+        pattern.cells = cells
+            .into_iter()
+            .map(|((x, y), cell)| {
+                let (x, y) = ((x - bounds.min_x) as usize, (y - bounds.min_y) as usize);
+                ((x, y), cell)
+            })
+            .collect();
+        pattern.cells.sort_by_key(|&((x, y), _)| (y, x));
+        pattern
+    }
+
+    /// Quadtree of the pattern at its origin. Patterns with more than two states use
+    /// multistate leaves.
+    pub fn from_pattern(pattern: &Pattern) -> Self {
+        let multistate = pattern.rule.is_some_and(|rule| rule.states() > 2)
+            || pattern.cells.iter().any(|(_, cell)| cell.state() > 1);
+        let (left, top) = pattern.origin;
+        let cells: Vec<_> = pattern
+            .cells
+            .iter()
+            .filter(|(_, cell)| !cell.is_dead() && (multistate || cell.is_alive()))
+            .map(|&((x, y), cell)| ((left + x as i64, top + y as i64), cell))
+            .collect();
+        let mut builder = Builder {
+            multistate,
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        };
+        let leaf_level = if multistate { 1 } else { 3 };
+        let level = (leaf_level..MAX_LEVEL)
+            .find(|&level| {
+                let half = 1i64 << (level - 1);
+                cells
+                    .iter()
+                    .all(|&((x, y), _)| (-half..half).contains(&x) && (-half..half).contains(&y))
+            })
+            .unwrap_or(MAX_LEVEL);
+        let half = 1i64 << (level - 1);
+        builder.build(level, -half, -half, cells);
+        Self {
+            name: pattern.name.clone(),
+            author: pattern.author.clone(),
+            comments: pattern.comments.clone(),
+            rule: pattern.rule,
+            generation: 0,
+            nodes: builder.nodes,
+        }
+    }
+
+    /// Loads the pattern onto the bounded grid of the topology if its bounding box fits,
+    /// and into an unbounded `HashLife` universe otherwise.
+    pub fn to_universe(&self, topology: Topology) -> Result<Box<dyn Universe>, UniverseError> {
+        let fits = self.bounding_box().is_none_or(|bounds| {
+            bounds.width() <= topology.width as u64 && bounds.height() <= topology.height as u64
+        });
+        if fits {
+            let pattern = self.to_pattern();
+            Ok(Box::new(pattern.to_game(
+                topology.width,
+                topology.height,
+                topology.wrap,
+            )))
+        } else {
+            Ok(Box::new(HashLife::from_macrocell(self)?))
+        }
+    }
+
+    // Coordinate of the top left corner of the root on both axes.
+    fn corner(&self) -> i64 {
+        match self.level() {
+            0 => 0,
+            level => -(1i64 << (level - 1)),
+        }
+    }
+
+    fn collect_cells(&self, id: usize, x: i64, y: i64, cells: &mut Vec<((i64, i64), Cell)>) {
+        if id == 0 {
+            return;
+        }
+        match self.nodes[id - 1] {
+            MacroNode::Bits(rows) => {
+                cells.extend(cells_of_bits(rows).map(|(dx, dy)| ((x + dx, y + dy), Cell::alive())))
+            }
+            MacroNode::States(states) => {
+                cells.extend((0..4).filter(|&i| states[i] != 0).map(|i| {
+                    (
+                        (x + (i % 2) as i64, y + (i / 2) as i64),
+                        Cell::dying(states[i]),
+                    )
+                }))
+            }
+            MacroNode::Inner(level, children) => {
+                let half = 1i64 << (level - 1);
+                for ((dx, dy), child) in quadrant_offsets(half).into_iter().zip(children) {
+                    self.collect_cells(child, x + dx, y + dy, cells);
+                }
+            }
+        }
+    }
+}
+
+fn quadrant_offsets(half: i64) -> [(i64, i64); 4] {
+    [(0, 0), (half, 0), (0, half), (half, half)]
+}
+
+fn cells_of_bits(rows: [u8; 8]) -> impl Iterator<Item = (i64, i64)> {
+    (0..64)
+        .filter(move |i| rows[i / 8] & (1 << (i % 8)) != 0)
+        .map(|i| ((i % 8) as i64, (i / 8) as i64))
+}
+
+fn include(bounds: Option<BoundingBox>, (x, y): (i64, i64)) -> Option<BoundingBox> {
+    match bounds {
+        Some(mut bounds) => {
+            bounds.include(x, y);
+            Some(bounds)
+        }
+        None => Some(BoundingBox::new(x, y)),
+    }
+}
+
+// Builds the quadtree of a set of cells, writing every distinct node once.
+struct Builder {
+    multistate: bool,
+    nodes: Vec<MacroNode>,
+    index: HashMap<MacroNode, usize>,
+}
+
+impl Builder {
+    fn build(&mut self, level: u8, x: i64, y: i64, cells: Vec<((i64, i64), Cell)>) -> usize {
+        if cells.is_empty() {
+            return 0;
+        }
+        let node = match (self.multistate, level) {
+            (false, 3) => {
+                let mut rows = [0; 8];
+                for ((cx, cy), _) in cells {
+                    rows[(cy - y) as usize] |= 1 << (cx - x);
+                }
+                MacroNode::Bits(rows)
+            }
+            (true, 1) => {
+                let mut states = [0; 4];
+                for ((cx, cy), cell) in cells {
+                    states[((cy - y) * 2 + cx - x) as usize] = cell.state();
+                }
+                MacroNode::States(states)
+            }
+            _ => {
+                let half = 1i64 << (level - 1);
+                let mut quadrants: [Vec<_>; 4] = Default::default();
+                for cell in cells {
+                    let ((cx, cy), _) = cell;
+                    let quadrant = usize::from(cy >= y + half) * 2 + usize::from(cx >= x + half);
+                    quadrants[quadrant].push(cell);
+                }
+                let mut children = [0; 4];
+                for (i, ((dx, dy), quadrant)) in quadrant_offsets(half)
+                    .into_iter()
+                    .zip(quadrants)
+                    .enumerate()
+                {
+                    children[i] = self.build(level - 1, x + dx, y + dy, quadrant);
+                }
+                MacroNode::Inner(level, children)
+            }
+        };
+        *self.index.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len()
+        })
+    }
+}
+
+/// Reads a macrocell file.
+pub fn parse(s: &str) -> Result<Macrocell, MacrocellError> {
+    let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+    if !lines
+        .next()
+        .is_some_and(|(_, line)| line.starts_with(HEADER))
+    {
+        return Err(MacrocellError::MissingHeader);
+    }
+    let mut macrocell = Macrocell::default();
+    for (number, line) in lines {
+        let invalid = || MacrocellError::InvalidLine(number, line.to_string());
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let text = chars.as_str().trim().to_string();
+            match kind {
+                Some('N') => macrocell.name = Some(text),
+                Some('O') => macrocell.author = Some(text),
+                Some('R') => {
+                    let rule = text
+                        .parse()
+                        .map_err(|e| MacrocellError::InvalidRule(number, e))?;
+                    macrocell.rule = Some(rule);
+                }
+                Some('G') => macrocell.generation = text.parse().map_err(|_| invalid())?,
+                Some('C' | 'c') => macrocell.comments.push(text),
+                // Golly skips any other comment line, e.g. its `#FRAMES` movie data.
+                _ => macrocell.comments.push(line[1..].trim().to_string()),
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let node = if line.starts_with(['.', '*', '$']) {
+            let mut rows = [0u8; 8];
+            let (mut x, mut y) = (0, 0);
+            for (column, c) in line.char_indices().map(|(i, c)| (i + 1, c)) {
+                match c {
+                    '.' | '*' if x >= 8 || y >= 8 => return Err(invalid()),
+                    '.' => x += 1,
+                    '*' => {
+                        rows[y] |= 1 << x;
+                        x += 1;
+                    }
+                    '$' => (x, y) = (0, y + 1),
+                    c => return Err(MacrocellError::UnexpectedChar(number, column, c)),
+                }
+            }
+            MacroNode::Bits(rows)
+        } else {
+            let numbers: Vec<usize> = line
+                .split_whitespace()
+                .map(|n| n.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid())?;
+            let [level, nw, ne, sw, se] = numbers[..] else {
+                return Err(invalid());
+            };
+            match level {
+                1 => {
+                    let states = [nw, ne, sw, se].map(|state| state.min(256));
+                    let limit = macrocell.rule.map_or(256, |rule| rule.states() as usize);
+                    if let Some(&state) = states.iter().find(|&&state| state >= limit) {
+                        return Err(MacrocellError::InvalidState(
+                            number,
+                            state.min(u8::MAX as usize) as u8,
+                        ));
+                    }
+                    MacroNode::States(states.map(|state| state as u8))
+                }
+                2..=62 => {
+                    let children = [nw, ne, sw, se];
+                    for child in children {
+                        let valid = child == 0
+                            || macrocell
+                                .nodes
+                                .get(child - 1)
+                                .is_some_and(|node| node.level() as usize == level - 1);
+                        if !valid {
+                            return Err(MacrocellError::InvalidReference(number, child));
+                        }
+                    }
+                    MacroNode::Inner(level as u8, children)
+                }
+                _ => return Err(invalid()),
+            }
+        };
+        macrocell.nodes.push(node);
+    }
+    Ok(macrocell)
+}
+
+/// Writes a macrocell file, every distinct node once.
+pub fn write(macrocell: &Macrocell) -> String {
+    let mut out = format!("{} (game_of_life)\n", HEADER);
+    if let Some(name) = &macrocell.name {
+        out.push_str(&format!("#N {}\n", name));
+    }
+    if let Some(author) = &macrocell.author {
+        out.push_str(&format!("#O {}\n", author));
+    }
+    for comment in &macrocell.comments {
+        out.push_str(&format!("#C {}\n", comment));
+    }
+    if let Some(rule) = macrocell.rule {
+        out.push_str(&format!("#R {}\n", rule));
+    }
+    if macrocell.generation > 0 {
+        out.push_str(&format!("#G {}\n", macrocell.generation));
+    }
+    for node in &macrocell.nodes {
+        match node {
+            MacroNode::Bits(rows) => {
+                let end = rows.iter().rposition(|&row| row != 0).map_or(0, |y| y + 1);
+                for &row in &rows[..end] {
+                    let width = 8 - row.leading_zeros() as usize;
+                    out.extend((0..width).map(|x| if row & (1 << x) != 0 { '*' } else { '.' }));
+                    out.push('$');
+                }
+            }
+            MacroNode::States(states) => {
+                out.push_str(&format!(
+                    "1 {} {} {} {}",
+                    states[0], states[1], states[2], states[3]
+                ));
+            }
+            MacroNode::Inner(level, [nw, ne, sw, se]) => {
+                out.push_str(&format!("{} {} {} {} {}", level, nw, ne, sw, se));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hashlife::HashLife,
+        macrocell::{parse, write, Macrocell, MacrocellError},
+        pattern::Pattern,
+        rule::Rule,
+        topology::Topology,
+        Cell,
+    };
+
+    const GLIDER: &str = "[M2] (golly 4.2)\n#R B3/S23\n.*$..*$***$\n4 0 0 0 1\n";
+    const GLIDER_LEAF: &str = "$$$$.....*$......*$....***$\n";
+
+    #[test]
+    fn parse_test() {
+        let glider = parse(GLIDER).unwrap();
+        assert_eq!(glider.rule, Some(Rule::conway()));
+        assert_eq!(glider.level(), 4);
+        let pattern = glider.to_pattern();
+        assert_eq!(pattern.origin, (0, 0));
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(
+            pattern.cells.iter().map(|&(p, _)| p).collect::<Vec<_>>(),
+            vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+        );
+
+        // The smallest root that holds the glider is a single leaf.
+        let written = write(&Macrocell::from_pattern(&pattern));
+        assert_eq!(
+            written,
+            format!("[M2] (game_of_life)\n#R B3/S23\n{}", GLIDER_LEAF)
+        );
+    }
+
+    #[test]
+    fn round_trip_test() {
+        // Two blocks far apart share one leaf.
+        let pattern = Pattern {
+            name: Some("Blocks".to_string()),
+            comments: vec!["Far apart".to_string()],
+            origin: (-1000, 5),
+            width: 2002,
+            height: 2,
+            cells: [
+                (0, 0),
+                (1, 0),
+                (2000, 0),
+                (2001, 0),
+                (0, 1),
+                (1, 1),
+                (2000, 1),
+                (2001, 1),
+            ]
+            .map(|p| (p, Cell::alive()))
+            .to_vec(),
+            ..Pattern::default()
+        };
+        let macrocell = Macrocell::from_pattern(&pattern);
+        assert_eq!(macrocell.level(), 11);
+        let written = write(&macrocell);
+        assert_eq!(
+            written
+                .lines()
+                .filter(|line| line.starts_with(['.', '*', '$']))
+                .count(),
+            1
+        );
+        assert_eq!(parse(&written).unwrap().to_pattern(), pattern);
+
+        let multistate = Pattern {
+            rule: Some("B2/S/C3".parse().unwrap()),
+            origin: (3, -2),
+            width: 2,
+            height: 1,
+            cells: vec![((0, 0), Cell::alive()), ((1, 0), Cell::dying(2))],
+            ..Pattern::default()
+        };
+        let written = write(&Macrocell::from_pattern(&multistate));
+        assert!(written.contains("\n1 0 1 0 0\n") && written.contains("\n1 2 0 0 0\n"));
+        assert_eq!(parse(&written).unwrap().to_pattern(), multistate);
+    }
+
+    #[test]
+    fn universe_test() {
+        let glider = parse(GLIDER).unwrap();
+        let mut life = HashLife::from_macrocell(&glider).unwrap();
+        life.step(4);
+        let mut cells = life.live_cells();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(cells, vec![(2, 1), (3, 2), (1, 3), (2, 3), (3, 3)]);
+        let moved = life.to_macrocell();
+        assert_eq!(moved.generation, 4);
+        assert_eq!(moved.to_pattern().origin, (1, 1));
+        assert_eq!(
+            HashLife::from_macrocell(&moved).unwrap().live_cells(),
+            life.live_cells()
+        );
+
+        let small: Topology = "T10,10".parse().unwrap();
+        let mut grid = glider.to_universe(small).unwrap();
+        assert_eq!(
+            grid.bounding_box().map(|b| (b.min_x, b.min_y)),
+            Some((3, 3))
+        );
+        grid.step(40);
+        assert_eq!(grid.population(), 5);
+        let tiny: Topology = "T2,2".parse().unwrap();
+        let mut plane = glider.to_universe(tiny).unwrap();
+        plane.step(40);
+        assert!(plane.is_alive(11, 10));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(parse("4 0 0 0 0\n"), Err(MacrocellError::MissingHeader));
+        assert_eq!(
+            parse("[M2]\n.*$\n5 0 0 0 1\n"),
+            Err(MacrocellError::InvalidReference(3, 1))
+        );
+        assert_eq!(
+            parse("[M2]\n.*$\n4 0 2 0 1\n"),
+            Err(MacrocellError::InvalidReference(3, 2))
+        );
+        assert_eq!(
+            parse("[M2]\n.*x$\n"),
+            Err(MacrocellError::UnexpectedChar(2, 3, 'x'))
+        );
+        assert_eq!(
+            parse("[M2]\n#R B2/S/C3\n1 0 3 0 1\n"),
+            Err(MacrocellError::InvalidState(3, 3))
+        );
+        assert_eq!(
+            parse("[M2]\n.........*$\n"),
+            Err(MacrocellError::InvalidLine(2, ".........*$".to_string()))
+        );
+    }
+}
@@ -1,180 +1,164 @@
+mod cli;
+
+use cli::{Options, USAGE};
+use game_of_life::{
+    census::Census,
+    history::Cycle,
+    pattern::{Format, Pattern},
+    search::Search,
+    soup::Soup,
+    GameOfLife,
+};
 use rand::prelude::random;
-use std::{fmt::Display, time::Duration};
+use std::{process::exit, time::Duration};
 
-pub enum WrapMode {
-    Wrap,
-    NoWrap,
-}
-
-#[derive(Default, Clone, Copy)]
-pub struct Cell(bool);
-
-impl Cell {
-    fn alive() -> Self {
-        Cell(true)
-    }
-
-    fn dead() -> Self {
-        Cell(false)
-    }
+// Generations remembered to notice the field repeating.
+const HISTORY: usize = 256;
+// Longest period looked for when naming objects for the census.
+const CENSUS_PERIOD: u64 = 256;
+// Generations a soup of a search gets to settle.
+const SEARCH_GENERATIONS: u64 = 10_000;
 
-    fn is_alive(&self) -> bool {
-        self.0
-    }
+fn clear_screen() {
+    print!("\x1B[2J\x1B[1;1H");
 }
 
-impl Display for Cell {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", if self.0 { '#' } else { ' ' })
-    }
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", message);
+    exit(1);
 }
 
-pub struct GameOfLife {
-    width: usize,
-    height: usize,
-    field: Vec<Cell>,
-    wrap: WrapMode,
+fn describe(cycle: Cycle) -> String {
+    let what = match cycle {
+        _ if cycle.is_translation() => format!(
+            "moves by ({}, {}) every {} generations",
+            cycle.displacement.0, cycle.displacement.1, cycle.period
+        ),
+        _ if cycle.is_still_life() => "is stable".to_string(),
+        _ => format!("oscillates with period {}", cycle.period),
+    };
+    format!(
+        "from generation {} on, the field {}",
+        cycle.generation - cycle.period,
+        what
+    )
 }
 
-impl GameOfLife {
-    pub fn new(width: usize, height: usize, wrap: WrapMode) -> Self {
-        Self {
-            width,
-            height,
-            field: GameOfLife::generate_field(width * height),
-            wrap,
+fn build_game(options: &Options) -> GameOfLife {
+    let topology = options.topology;
+    let game = match &options.input {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+            let pattern = Pattern::parse(&content)
+                .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+            pattern.to_game(topology.width, topology.height, topology.wrap)
         }
-    }
-    pub fn update(&mut self) {
-        self.field = self
-            .field
-            .clone()
-            .into_iter()
-            .enumerate()
-            .map(|(index, c)| {
-                let neighbors = self.count_neighbors(index);
-                match neighbors {
-                    2 => c,
-                    3 => Cell::alive(),
-                    _ => Cell::dead(),
-                }
-            })
-            .collect()
-    }
-
-    pub fn get(&self, x: isize, y: isize) -> Option<&Cell> {
-        match self.wrap {
-            WrapMode::Wrap => {
-                let x = (x + self.width as isize) as usize % self.width;
-                let y = (y + self.height as isize) as usize % self.height;
-                let index = self.index(x, y);
-                self.field.get(index)
-            }
-            WrapMode::NoWrap => {
-                if x < 0 || x >= self.width as isize || y < 0 || y >= self.height as isize {
-                    None
-                } else {
-                    let index = self.index(x as usize, y as usize);
-                    self.field.get(index)
-                }
-            }
+        None => {
+            let soup = Soup::new(options.seed.unwrap_or_else(random))
+                .with_density(options.density)
+                .with_symmetry(options.symmetry);
+            GameOfLife::from_soup(topology.width, topology.height, topology.wrap, &soup)
         }
-    }
-
-    pub fn is_alive(&self, x: isize, y: isize) -> bool {
-        self.get(x, y).map(|c| c.is_alive()).unwrap_or(false)
-    }
-
-    pub fn print_neighbors(&self) {
-        self.field.iter().enumerate().for_each(|(i, _)| {
-            if i > 0 && i % self.width == 0 {
-                println!();
-            }
-            print!("{}", self.count_neighbors(i));
-        })
-    }
-
-    fn generate_field(count: usize) -> Vec<Cell> {
-        (0..count).map(|_| Cell(random::<bool>())).collect()
-    }
-
-    fn count_neighbors(&self, index: usize) -> usize {
-        let (x, y) = self.index_to_coords(index);
-        let x = x as isize;
-        let y = y as isize;
-
-        (-1..=1)
-            .flat_map(|i| (-1..=1).map(move |j| (i, j)))
-            .filter(|(i, j)| *i != 0 || *j != 0)
-            .filter(|(i, j)| self.is_alive(x + i, y + j))
-            .count()
-    }
-
-    fn index_to_coords(&self, index: usize) -> (usize, usize) {
-        let x = index % self.width;
-        let y = index / self.width;
-
-        (x, y)
-    }
-
-    fn index(&self, x: usize, y: usize) -> usize {
-        y * self.width + x
-    }
+    };
+    let game = match options.rule {
+        Some(rule) => game.with_rule(rule),
+        None => game,
+    };
+    game.with_history(HISTORY)
 }
 
-impl Display for GameOfLife {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.field.iter().enumerate().try_for_each(|(i, c)| {
-            if i > 0 && i % self.width == 0 {
-                writeln!(f)?;
-            }
-            write!(f, "{}", c)
-        })
+fn search(options: &Options, soups: u64) {
+    let root = options
+        .root
+        .clone()
+        .unwrap_or_else(|| format!("{:016x}_", random::<u64>()));
+    let mut search = Search::new(root, soups)
+        .with_rule(options.rule.unwrap_or_default())
+        .with_density(options.density)
+        .with_symmetry(options.symmetry)
+        .with_generations(options.generations.unwrap_or(SEARCH_GENERATIONS));
+    if let Some(threads) = options.threads {
+        search = search.with_threads(threads);
+    }
+    // Soups are 16x16 like apgsearch's unless a size was asked for.
+    if options.size_given {
+        search = search.with_size(options.topology.width, options.topology.height);
+    }
+    let report = search.run().unwrap_or_else(|e| fail(e));
+    print!("{}", report);
+
+    let Some(dir) = &options.report_dir else {
+        return;
+    };
+    let write = |name: &str, content: String| {
+        let path = dir.join(name);
+        std::fs::write(&path, content)
+            .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e)));
+    };
+    std::fs::create_dir_all(dir).unwrap_or_else(|e| fail(format!("{}: {}", dir.display(), e)));
+    write("report.txt", report.to_string());
+    for find in &report.finds {
+        write(
+            &format!("{}.rle", find.seed),
+            Format::Rle.write(&find.pattern),
+        );
     }
 }
 
-fn clear_screen() {
-    print!("\x1B[2J\x1B[1;1H");
-}
-
 fn main() {
-    let mut game = GameOfLife::new(30, 30, WrapMode::Wrap);
-    while game.field.iter().any(|c| c.is_alive()) {
-        let start_time = std::time::Instant::now();
+    let options = Options::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        exit(2);
+    });
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    if let Some(soups) = options.search {
+        search(&options, soups);
+        return;
+    }
+    let mut game = build_game(&options);
+    let limit = options.generations.unwrap_or(u64::MAX);
+
+    if options.headless {
+        (0..limit).for_each(|_| game.update());
+        if let Some(cycle) = game.cycle() {
+            eprintln!("{}", describe(cycle));
+        }
+    } else {
+        let frame = Duration::from_secs_f64(1.0 / options.fps);
+        let repeated = |game: &GameOfLife| !options.ignore_cycles && game.cycle().is_some();
+        while !game.is_extinct() && !repeated(&game) && game.generation() < limit {
+            let start_time = std::time::Instant::now();
+            clear_screen();
+            println!("{}", game);
+            game.update();
+            let end_time = std::time::Instant::now();
+            std::thread::sleep(frame.saturating_sub(end_time - start_time));
+        }
         clear_screen();
         println!("{}", game);
-        game.update();
-        let end_time = std::time::Instant::now();
-        std::thread::sleep(
-            Duration::from_secs_f32(1.0 / 30.0).saturating_sub(end_time - start_time),
-        );
+        if let Some(cycle) = game.cycle() {
+            println!("{}", describe(cycle));
+        }
     }
-    clear_screen();
-    println!("{}", game);
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::{GameOfLife, WrapMode};
 
-    #[test]
-    fn index_to_coords_test() {
-        let game = GameOfLife::new(10, 5, WrapMode::NoWrap);
-
-        assert_eq!(game.index_to_coords(0), (0, 0));
-        assert_eq!(game.index_to_coords(1), (1, 0));
-        assert_eq!(game.index_to_coords(5), (5, 0));
-        assert_eq!(game.index_to_coords(10), (0, 1));
-        assert_eq!(game.index_to_coords(49), (9, 4));
+    if options.census {
+        let census = Census::from_game(&game, CENSUS_PERIOD).unwrap_or_else(|e| fail(e));
+        if options.headless {
+            eprint!("{}", census);
+        } else {
+            print!("{}", census);
+        }
     }
 
-    #[test]
-    fn index_test() {
-        let game = GameOfLife::new(10, 5, WrapMode::Wrap);
-        assert_eq!(game.index(0, 0), 0);
-        assert_eq!(game.index(1, 0), 1);
-        assert_eq!(game.index(5, 0), 5);
-        assert_eq!(game.index(0, 1), 10);
-        assert_eq!(game.index(9, 4), 49);
+    let output = game.to_pattern_string(options.output_format());
+    match &options.output {
+        Some(path) => std::fs::write(path, output)
+            .unwrap_or_else(|e| fail(format!("{}: {}", path.display(), e))),
+        None if options.headless => print!("{}", output),
+        None => {}
     }
 }
@@ -0,0 +1,313 @@
+//! Neighborhoods of outer totalistic and Larger than Life rules, and counting their live
+//! cells.
+
+use std::fmt::Display;
+
+use crate::geometry::Geometry;
+
+/// Shape of the cells a neighborhood covers around its center.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// Square of side `2r + 1`.
+    Moore,
+    /// Diamond of cells at most `r` steps away horizontally and vertically.
+    VonNeumann,
+    /// Cells whose centers lie within a disc of radius `r + 1/2`.
+    Circular,
+    /// Hexagon of cells at most `r` steps away on the hexagonal grid.
+    Hexagonal,
+    /// For radius 1, the 12 triangles sharing a vertex with the center one.
+    Triangular,
+}
+
+impl Shape {
+    /// Grid the shape is meant for.
+    pub fn geometry(&self) -> Geometry {
+        match self {
+            Shape::Hexagonal => Geometry::Hexagonal,
+            Shape::Triangular => Geometry::Triangular,
+            _ => Geometry::Square,
+        }
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Shape::Moore => 'M',
+            Shape::VonNeumann => 'N',
+            Shape::Circular => 'C',
+            Shape::Hexagonal => 'H',
+            Shape::Triangular => 'T',
+        };
+        write!(f, "{}", c)
+    }
+}
+
+/// Cells whose live ones are counted for outer totalistic and Larger than Life rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Neighborhood {
+    shape: Shape,
+    radius: usize,
+    middle: bool,
+}
+
+impl Neighborhood {
+    /// Neighborhood of the given shape and radius, with the center cell if `middle` is set.
+    pub fn new(shape: Shape, radius: usize, middle: bool) -> Self {
+        Self {
+            shape,
+            radius,
+            middle,
+        }
+    }
+
+    /// Shape of the covered cells.
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// How far the neighborhood reaches.
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    /// Whether the center cell counts itself.
+    pub fn middle(&self) -> bool {
+        self.middle
+    }
+
+    /// Number of cells covered, the center one included if it counts.
+    pub fn size(&self) -> usize {
+        let r = self.radius as isize;
+        let cells: isize = (-r..=r)
+            .map(|dy| {
+                let (from, to) = self.span(dy, true);
+                to - from + 1
+            })
+            .sum();
+        cells as usize - usize::from(!self.middle)
+    }
+
+    /// Offsets of the cells in the neighborhood of a cell, which points up if the grid is
+    /// triangular.
+    pub fn offsets(&self, up: bool) -> Vec<(isize, isize)> {
+        let r = self.radius as isize;
+        (-r..=r)
+            .flat_map(|dy| {
+                let (from, to) = self.span(dy, up);
+                (from..=to).map(move |dx| (dx, dy))
+            })
+            .filter(|&offset| self.middle || offset != (0, 0))
+            .collect()
+    }
+
+    /// Offsets of the cells whose neighborhood contains a cell, which points up if the grid is
+    /// triangular. Only triangles pointing the same way are mirrored versions of each other,
+    /// every other shape is its own reflection.
+    pub fn reverse_offsets(&self, up: bool) -> Vec<(isize, isize)> {
+        let odd = |(dx, dy): (isize, isize)| (dx + dy).rem_euclid(2) == 1;
+        let mut offsets: Vec<_> = self
+            .offsets(!up)
+            .into_iter()
+            .filter(|&offset| !odd(offset))
+            .chain(self.offsets(up).into_iter().filter(|&offset| odd(offset)))
+            .collect();
+        offsets.sort_unstable();
+        offsets
+    }
+
+    /// Counts live cells in the neighborhood of every cell of a `width * height` field.
+    ///
+    /// The field is first copied into a buffer padded by `radius` on every side, so `alive`
+    /// is the only place that has to know about wrapping. Moore neighborhoods are then
+    /// summed in constant time from a summed-area table, other shapes add up one prefix-sum
+    /// span per row, so a cell costs O(1) or O(radius) rather than O(radius^2).
+    pub fn count(
+        &self,
+        width: usize,
+        height: usize,
+        alive: impl Fn(isize, isize) -> bool,
+    ) -> Vec<usize> {
+        let mut counts = Vec::new();
+        self.count_into(width, height, alive, &mut Vec::new(), &mut counts);
+        counts
+    }
+
+    /// Like [`Neighborhood::count`], but builds the summed-area table in `table` and writes
+    /// the counts to `counts`, so stepping a field doesn't allocate once they are big
+    /// enough.
+    pub fn count_into(
+        &self,
+        width: usize,
+        height: usize,
+        alive: impl Fn(isize, isize) -> bool,
+        table: &mut Vec<usize>,
+        counts: &mut Vec<usize>,
+    ) {
+        let r = self.radius as isize;
+        // Triangular neighborhoods reach further sideways than up and down.
+        let pad = (-r..=r)
+            .flat_map(|dy| [self.span(dy, true), self.span(dy, false)])
+            .map(|(from, to)| from.abs().max(to.abs()))
+            .max()
+            .unwrap_or(r) as usize;
+        let padded_width = width + 2 * pad;
+        let padded_height = height + 2 * self.radius;
+        let stride = padded_width + 1;
+
+        // table[(y + 1) * stride + x + 1] holds the sum of the padded rectangle (0, 0)..=(x, y)
+        // for Moore neighborhoods, and the sum of row y up to column x otherwise.
+        table.clear();
+        table.resize(stride * (padded_height + 1), 0);
+        for py in 0..padded_height {
+            let mut row = 0;
+            for px in 0..padded_width {
+                row += usize::from(alive(px as isize - pad as isize, py as isize - r));
+                let above = match self.shape {
+                    Shape::Moore => table[py * stride + px + 1],
+                    _ => 0,
+                };
+                table[(py + 1) * stride + px + 1] = row + above;
+            }
+        }
+
+        let span = |y: usize, from: usize, to: usize| -> usize {
+            table[(y + 1) * stride + to + 1] - table[(y + 1) * stride + from]
+        };
+
+        counts.clear();
+        counts.extend(
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let (cx, cy) = (x + pad, y + self.radius);
+                    let sum = match self.shape {
+                        Shape::Moore => {
+                            let (x0, y0) = (x, y);
+                            let (x1, y1) = (x + 2 * self.radius + 1, y + 2 * self.radius + 1);
+                            table[y1 * stride + x1] + table[y0 * stride + x0]
+                                - table[y0 * stride + x1]
+                                - table[y1 * stride + x0]
+                        }
+                        _ => {
+                            let up = Geometry::points_up(x as isize, y as isize);
+                            (-r..=r)
+                                .map(|dy| {
+                                    let (from, to) = self.span(dy, up);
+                                    span(
+                                        (cy as isize + dy) as usize,
+                                        (cx as isize + from) as usize,
+                                        (cx as isize + to) as usize,
+                                    )
+                                })
+                                .sum()
+                        }
+                    };
+                    if self.middle {
+                        sum
+                    } else {
+                        sum - usize::from(alive(x as isize, y as isize))
+                    }
+                }),
+        );
+    }
+
+    // Horizontal offsets covered by the neighborhood in row `dy`, for a cell that points up
+    // if the grid is triangular.
+    fn span(&self, dy: isize, up: bool) -> (isize, isize) {
+        let r = self.radius as isize;
+        let w = match self.shape {
+            Shape::Moore => r,
+            Shape::VonNeumann => r - dy.abs(),
+            Shape::Circular => {
+                let limit = r * r + r - dy * dy;
+                (0..=r)
+                    .take_while(|dx| dx * dx <= limit)
+                    .last()
+                    .unwrap_or(0)
+            }
+            Shape::Hexagonal => return ((dy - r).max(-r), (dy + r).min(r)),
+            Shape::Triangular => {
+                // The row on the side of the base reaches further than the one at the apex.
+                let base = if up { dy > 0 } else { dy < 0 };
+                2 * r - dy.abs() + isize::from(base)
+            }
+        };
+        (-w, w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        geometry::Geometry,
+        neighborhood::{Neighborhood, Shape},
+    };
+
+    #[test]
+    fn size_test() {
+        assert_eq!(Neighborhood::new(Shape::Moore, 1, false).size(), 8);
+        assert_eq!(Neighborhood::new(Shape::Moore, 2, true).size(), 25);
+        assert_eq!(Neighborhood::new(Shape::VonNeumann, 2, false).size(), 12);
+        assert_eq!(Neighborhood::new(Shape::Circular, 2, false).size(), 20);
+        assert_eq!(Neighborhood::new(Shape::Hexagonal, 1, false).size(), 6);
+        assert_eq!(Neighborhood::new(Shape::Triangular, 1, false).size(), 12);
+    }
+
+    #[test]
+    fn reverse_offsets_test() {
+        for shape in [Shape::Moore, Shape::Hexagonal, Shape::Triangular] {
+            let neighborhood = Neighborhood::new(shape, 1, false);
+            for up in [true, false] {
+                let mut expected: Vec<_> = (-3..=3)
+                    .flat_map(|dy| (-3..=3).map(move |dx| (dx, dy)))
+                    .filter(|&(dx, dy)| {
+                        let up = if (dx + dy) % 2 == 0 { up } else { !up };
+                        neighborhood.offsets(up).contains(&(-dx, -dy))
+                    })
+                    .collect();
+                expected.sort_unstable();
+                assert_eq!(neighborhood.reverse_offsets(up), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn count_matches_naive_test() {
+        let (width, height) = (7, 6);
+        let alive = |x: isize, y: isize| {
+            let x = x.rem_euclid(width as isize);
+            let y = y.rem_euclid(height as isize);
+            (x * 3 + y * 5) % 7 < 3
+        };
+        for shape in [
+            Shape::Moore,
+            Shape::VonNeumann,
+            Shape::Circular,
+            Shape::Hexagonal,
+            Shape::Triangular,
+        ] {
+            for radius in 1..=3 {
+                let neighborhood = Neighborhood::new(shape, radius, false);
+                let r = radius as isize;
+                let expected: Vec<usize> = (0..height as isize)
+                    .flat_map(|y| (0..width as isize).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        (-r..=r)
+                            .flat_map(|dy| (-3 * r..=3 * r).map(move |dx| (dx, dy)))
+                            .filter(|&(dx, dy)| dx != 0 || dy != 0)
+                            .filter(|&(dx, dy)| {
+                                let up = Geometry::points_up(x, y);
+                                let (from, to) = neighborhood.span(dy, up);
+                                (from..=to).contains(&dx)
+                            })
+                            .filter(|&(dx, dy)| alive(x + dx, y + dy))
+                            .count()
+                    })
+                    .collect();
+                assert_eq!(neighborhood.count(width, height, alive), expected);
+            }
+        }
+    }
+}
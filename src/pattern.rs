@@ -0,0 +1,303 @@
+//! Patterns read from and written to files in any supported format.
+
+use std::{fmt::Display, path::Path, str::FromStr};
+
+use crate::{
+    life::{self, LifeError},
+    macrocell::{self, Macrocell, MacrocellError},
+    plaintext::{self, PlaintextError},
+    rle::{self, RleError},
+    rule::Rule,
+    topology::WrapMode,
+    Cell, GameOfLife,
+};
+
+/// File formats patterns can be read from and written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Run-length encoded, as used by Golly and LifeWiki.
+    Rle,
+    /// LifeWiki's `.cells`.
+    Plaintext,
+    /// Rows of `.` and `*` placed with `#P` lines.
+    Life105,
+    /// Coordinates of live cells, one pair per line.
+    Life106,
+    /// Golly's `.mc` quadtrees.
+    Macrocell,
+}
+
+impl Format {
+    /// Every format.
+    pub const ALL: [Format; 5] = [
+        Format::Rle,
+        Format::Plaintext,
+        Format::Life105,
+        Format::Life106,
+        Format::Macrocell,
+    ];
+
+    /// Format usually stored under the extension of the path. `.lif` files are written
+    /// as Life 1.06.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" => Some(Format::Plaintext),
+            "lif" | "life" => Some(Format::Life106),
+            "mc" => Some(Format::Macrocell),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first line of a file.
+    pub fn detect(s: &str) -> Self {
+        let first = s.lines().map(str::trim).find(|line| !line.is_empty());
+        match first {
+            Some(line) if line.starts_with("#Life 1.05") => Format::Life105,
+            Some(line) if line.starts_with("#Life 1.06") => Format::Life106,
+            Some(line) if line.starts_with("[M2]") => Format::Macrocell,
+            // RLE files start with comments or the `x = ...` header.
+            Some(line) if line.starts_with(['#', 'x']) => Format::Rle,
+            _ => Format::Plaintext,
+        }
+    }
+
+    /// Reads a pattern in this format.
+    pub fn parse(&self, s: &str) -> Result<Pattern, PatternError> {
+        Ok(match self {
+            Format::Rle => rle::parse(s)?,
+            Format::Plaintext => plaintext::parse(s)?,
+            Format::Life105 => life::parse_105(s)?,
+            Format::Life106 => life::parse_106(s)?,
+            Format::Macrocell => macrocell::parse(s)?.to_pattern(),
+        })
+    }
+
+    /// Writes the pattern in this format.
+    pub fn write(&self, pattern: &Pattern) -> String {
+        match self {
+            Format::Rle => rle::write(pattern),
+            Format::Plaintext => plaintext::write(pattern),
+            Format::Life105 => life::write_105(pattern),
+            Format::Life106 => life::write_106(pattern),
+            Format::Macrocell => macrocell::write(&Macrocell::from_pattern(pattern)),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .into_iter()
+            .find(|format| format.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| PatternError::UnknownFormat(s.to_string()))
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Format::Rle => "rle",
+            Format::Plaintext => "cells",
+            Format::Life105 => "life105",
+            Format::Life106 => "life106",
+            Format::Macrocell => "mc",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Error reading a pattern file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PatternError {
+    /// Format name or file extension that isn't known.
+    UnknownFormat(String),
+    /// Error reading an RLE file.
+    Rle(RleError),
+    /// Error reading a plaintext file.
+    Plaintext(PlaintextError),
+    /// Error reading a Life 1.05 or 1.06 file.
+    Life(LifeError),
+    /// Error reading a macrocell file.
+    Macrocell(MacrocellError),
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::UnknownFormat(s) => write!(f, "unknown pattern format '{}'", s),
+            PatternError::Rle(e) => write!(f, "RLE: {}", e),
+            PatternError::Plaintext(e) => write!(f, "plaintext: {}", e),
+            PatternError::Life(e) => write!(f, "Life 1.0x: {}", e),
+            PatternError::Macrocell(e) => write!(f, "macrocell: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl From<RleError> for PatternError {
+    fn from(e: RleError) -> Self {
+        PatternError::Rle(e)
+    }
+}
+
+impl From<PlaintextError> for PatternError {
+    fn from(e: PlaintextError) -> Self {
+        PatternError::Plaintext(e)
+    }
+}
+
+impl From<LifeError> for PatternError {
+    fn from(e: LifeError) -> Self {
+        PatternError::Life(e)
+    }
+}
+
+impl From<MacrocellError> for PatternError {
+    fn from(e: MacrocellError) -> Self {
+        PatternError::Macrocell(e)
+    }
+}
+
+/// Pattern read from or written to a file, with the metadata the formats can carry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pattern {
+    /// Name of the pattern, if the file gives one.
+    pub name: Option<String>,
+    /// Author of the pattern, if the file gives one.
+    pub author: Option<String>,
+    /// Free-form comment lines.
+    pub comments: Vec<String>,
+    /// Rule the pattern is meant for, if the file gives one.
+    pub rule: Option<Rule>,
+    /// Coordinates of the top left corner, for formats that place patterns on a plane.
+    pub origin: (i64, i64),
+    /// Number of columns.
+    pub width: usize,
+    /// Number of rows.
+    pub height: usize,
+    /// Cells that aren't dead, relative to the top left corner.
+    pub cells: Vec<((usize, usize), Cell)>,
+}
+
+impl Pattern {
+    /// Reads a pattern in any supported format, detected from the content.
+    pub fn parse(s: &str) -> Result<Self, PatternError> {
+        Format::detect(s).parse(s)
+    }
+
+    /// Pattern of every cell of the field.
+    pub fn from_game(game: &GameOfLife) -> Self {
+        Self {
+            rule: Some(*game.rule()),
+            width: game.width,
+            height: game.height,
+            cells: (0..game.field.len())
+                .filter(|&i| !game.field[i].is_dead())
+                .map(|i| (game.index_to_coords(i), game.field[i]))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Centers the pattern on an empty `width * height` field, cutting off what doesn't
+    /// fit. The field runs the pattern's rule if it has one.
+    pub fn to_game(&self, width: usize, height: usize, wrap: WrapMode) -> GameOfLife {
+        let mut game =
+            GameOfLife::empty(width, height, wrap).with_rule(self.rule.unwrap_or_default());
+        let offset = |field: usize, pattern: usize| (field as isize - pattern as isize) / 2;
+        let (dx, dy) = (offset(width, self.width), offset(height, self.height));
+        for &((x, y), cell) in &self.cells {
+            let (x, y) = (x as isize + dx, y as isize + dy);
+            if (0..width as isize).contains(&x) && (0..height as isize).contains(&y) {
+                game.set(x as usize, y as usize, cell);
+            }
+        }
+        game
+    }
+
+    /// State of every cell, row by row.
+    pub(crate) fn rows(&self) -> Vec<Vec<Cell>> {
+        let mut rows = vec![vec![Cell::dead(); self.width]; self.height];
+        for &((x, y), cell) in &self.cells {
+            rows[y][x] = cell;
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        pattern::{Format, Pattern},
+        topology::WrapMode,
+        Cell, GameOfLife,
+    };
+
+    #[test]
+    fn game_round_trip_test() {
+        let pattern = Pattern {
+            width: 3,
+            height: 2,
+            cells: vec![((0, 0), Cell::alive()), ((2, 1), Cell::dying(2))],
+            ..Pattern::default()
+        };
+        let game = pattern.to_game(7, 6, WrapMode::Wrap);
+        assert_eq!(game.live_cells().collect::<Vec<_>>(), vec![(2, 2)]);
+        assert_eq!(game.get(4, 3), Some(&Cell::dying(2)));
+
+        let copy = Pattern::from_game(&GameOfLife::from_cells(3, 1, WrapMode::NoWrap, [(1, 0)]));
+        assert_eq!(copy.cells, vec![((1, 0), Cell::alive())]);
+        assert_eq!((copy.width, copy.height), (3, 1));
+    }
+
+    #[test]
+    fn detect_test() {
+        let glider = [
+            (
+                Format::Rle,
+                "#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n",
+            ),
+            (Format::Rle, "x = 3, y = 3\nbo$2bo$3o!\n"),
+            (Format::Plaintext, "!Name: Glider\n.O\n..O\nOOO\n"),
+            (Format::Plaintext, "\n.O\n..O\nOOO\n"),
+            (Format::Life105, "#Life 1.05\n#P -1 -1\n.*\n..*\n***\n"),
+            (Format::Life106, "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n"),
+            (Format::Life105, "\n#Life 1.05\n#P -1 -1\n.*\n..*\n***\n"),
+            (
+                Format::Life106,
+                "\n \n#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n",
+            ),
+            (
+                Format::Macrocell,
+                "[M2] (golly 4.2)\n.*$..*$***$\n4 0 0 0 1\n",
+            ),
+        ];
+        for (format, s) in glider {
+            assert_eq!(Format::detect(s), format);
+            let pattern = Pattern::parse(s).unwrap();
+            let game = pattern.to_game(5, 5, WrapMode::NoWrap);
+            let cells: Vec<_> = game.live_cells().collect();
+            assert_eq!(
+                cells,
+                vec![(2, 1), (3, 2), (1, 3), (2, 3), (3, 3)],
+                "{:?}",
+                format
+            );
+        }
+        assert!(Pattern::parse("#Life 1.06\n0\n").is_err());
+
+        assert_eq!("MC".parse(), Ok(Format::Macrocell));
+        assert_eq!(
+            Format::from_path(Path::new("glider.cells")),
+            Some(Format::Plaintext)
+        );
+        assert_eq!(Format::from_path(Path::new("glider")), None);
+    }
+}
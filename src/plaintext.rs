@@ -0,0 +1,122 @@
+//! LifeWiki's plaintext `.cells` pattern files.
+
+use std::fmt::Display;
+
+use crate::{pattern::Pattern, Cell};
+
+/// Error reading a plaintext file. Lines and columns count from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlaintextError {
+    /// Character other than `.` and `O` in a row of cells.
+    UnexpectedChar(usize, usize, char),
+}
+
+impl Display for PlaintextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlaintextError::UnexpectedChar(line, column, c) => write!(
+                f,
+                "line {}, column {}: unexpected character '{}'",
+                line, column, c
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlaintextError {}
+
+/// Reads LifeWiki's `.cells` format: `!` comments followed by rows of `.` and `O`.
+pub fn parse(s: &str) -> Result<Pattern, PlaintextError> {
+    let mut pattern = Pattern::default();
+    let mut rows = Vec::new();
+    for (number, line) in s
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+    {
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = Some(author.trim().to_string());
+            } else {
+                pattern.comments.push(comment.to_string());
+            }
+            continue;
+        }
+        let y = rows.len();
+        for (column, c) in line.char_indices().map(|(i, c)| (i + 1, c)) {
+            match c {
+                '.' => {}
+                'O' | '*' => pattern.cells.push(((column - 1, y), Cell::alive())),
+                c => return Err(PlaintextError::UnexpectedChar(number, column, c)),
+            }
+        }
+        rows.push(line.len());
+    }
+    // Trailing empty lines are not part of the pattern.
+    while rows.last() == Some(&0) {
+        rows.pop();
+    }
+    pattern.width = rows.iter().copied().max().unwrap_or(0);
+    pattern.height = rows.len();
+    Ok(pattern)
+}
+
+/// Writes the live cells as `O`, without the dead cells at the end of each row.
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("!Name: {}\n", name));
+    }
+    if let Some(author) = &pattern.author {
+        out.push_str(&format!("!Author: {}\n", author));
+    }
+    for comment in &pattern.comments {
+        out.push_str(&format!("!{}\n", comment));
+    }
+    for row in pattern.rows() {
+        let end = row.iter().rposition(|c| c.is_alive()).map_or(0, |i| i + 1);
+        out.extend(
+            row[..end]
+                .iter()
+                .map(|c| if c.is_alive() { 'O' } else { '.' }),
+        );
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plaintext::{parse, write, PlaintextError},
+        Cell,
+    };
+
+    #[test]
+    fn round_trip_test() {
+        let cells =
+            "!Name: Glider\n!Author: Richard K. Guy\n!The smallest spaceship.\n.O\n..O\nOOO\n";
+        let glider = parse(cells).unwrap();
+        assert_eq!(glider.name.as_deref(), Some("Glider"));
+        assert_eq!(glider.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(glider.comments, vec!["The smallest spaceship."]);
+        assert_eq!((glider.width, glider.height), (3, 3));
+        assert_eq!(glider.cells[0], ((1, 0), Cell::alive()));
+        assert_eq!(write(&glider), cells);
+
+        let gap = parse("O\n\n.O\n\n").unwrap();
+        assert_eq!((gap.width, gap.height), (2, 3));
+        assert_eq!(write(&gap), "O\n\n.O\n");
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(
+            parse("!Name: x\n.O\n.#\n"),
+            Err(PlaintextError::UnexpectedChar(3, 2, '#'))
+        );
+    }
+}
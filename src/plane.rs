@@ -0,0 +1,282 @@
+//! Unbounded engine storing only the cells that aren't dead.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    geometry::Geometry,
+    hensel,
+    rule::{Rule, RuleError},
+    Cell,
+};
+
+/// Smallest rectangle holding a set of cells, bounds included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    /// Leftmost column.
+    pub min_x: i64,
+    /// Topmost row.
+    pub min_y: i64,
+    /// Rightmost column.
+    pub max_x: i64,
+    /// Bottom row.
+    pub max_y: i64,
+}
+
+impl BoundingBox {
+    /// Box holding the single cell (x, y).
+    pub fn new(x: i64, y: i64) -> Self {
+        Self {
+            min_x: x,
+            min_y: y,
+            max_x: x,
+            max_y: y,
+        }
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> u64 {
+        self.max_x.abs_diff(self.min_x) + 1
+    }
+
+    /// Number of rows.
+    pub fn height(&self) -> u64 {
+        self.max_y.abs_diff(self.min_y) + 1
+    }
+
+    /// Grows the box to hold the cell (x, y).
+    pub fn include(&mut self, x: i64, y: i64) {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    fn on_edge(&self, x: i64, y: i64) -> bool {
+        x == self.min_x || x == self.max_x || y == self.min_y || y == self.max_y
+    }
+}
+
+/// Unbounded universe that only stores cells which are not dead, so patterns can travel
+/// arbitrarily far in any direction.
+#[derive(Debug, Clone, Default)]
+pub struct Plane {
+    cells: HashMap<(i64, i64), Cell>,
+    rule: Rule,
+    bounds: Option<BoundingBox>,
+}
+
+impl Plane {
+    /// Empty plane running B3/S23.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rules with B0 would fill the whole plane in one generation.
+    pub fn with_rule(mut self, rule: Rule) -> Result<Self, RuleError> {
+        let born = match rule.neighborhood() {
+            Some(_) => rule.next_counted(Cell::dead(), 0),
+            None => rule.next(Cell::dead(), 0),
+        };
+        if born.is_alive() {
+            return Err(RuleError::Unbounded);
+        }
+        self.rule = rule;
+        Ok(self)
+    }
+
+    /// Rule the plane runs.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Cell at the given coordinates.
+    pub fn get(&self, x: i64, y: i64) -> Cell {
+        self.cells.get(&(x, y)).copied().unwrap_or_default()
+    }
+
+    /// Whether the cell at the coordinates is alive.
+    pub fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.get(x, y).is_alive()
+    }
+
+    /// Sets the state of a cell, forgetting it if it is dead.
+    pub fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        if cell.is_dead() {
+            if self.cells.remove(&(x, y)).is_some() && self.bounds.is_some_and(|b| b.on_edge(x, y))
+            {
+                self.bounds = Self::bounds_of(self.cells.keys());
+            }
+        } else {
+            self.cells.insert((x, y), cell);
+            match &mut self.bounds {
+                Some(bounds) => bounds.include(x, y),
+                None => self.bounds = Some(BoundingBox::new(x, y)),
+            }
+        }
+    }
+
+    /// Number of live cells, not counting dying ones.
+    pub fn population(&self) -> usize {
+        self.cells.values().filter(|c| c.is_alive()).count()
+    }
+
+    /// Coordinates of the live cells, in no particular order.
+    pub fn live_cells(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.cells
+            .iter()
+            .filter(|(_, c)| c.is_alive())
+            .map(|(&position, _)| position)
+    }
+
+    /// Every cell that isn't dead, with its state.
+    pub fn cells(&self) -> impl Iterator<Item = ((i64, i64), Cell)> + '_ {
+        self.cells.iter().map(|(&position, &cell)| (position, cell))
+    }
+
+    /// Smallest box containing every cell that isn't dead.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounds
+    }
+
+    /// Advances the plane by one generation.
+    pub fn update(&mut self) {
+        let alive = self
+            .cells
+            .iter()
+            .filter(|(_, c)| c.is_alive())
+            .map(|(&position, _)| position);
+
+        let next: HashMap<(i64, i64), Cell> = match self.rule.neighborhood() {
+            Some(neighborhood) => {
+                let reverse = [
+                    neighborhood.reverse_offsets(false),
+                    neighborhood.reverse_offsets(true),
+                ];
+                let mut counts: HashMap<(i64, i64), usize> = HashMap::new();
+                for (x, y) in alive {
+                    let up = Geometry::points_up(x as isize, y as isize);
+                    for &(dx, dy) in &reverse[usize::from(up)] {
+                        *counts.entry((x + dx as i64, y + dy as i64)).or_default() += 1;
+                    }
+                }
+                self.cells
+                    .keys()
+                    .chain(counts.keys())
+                    .collect::<HashSet<_>>()
+                    .into_iter()
+                    .map(|&(x, y)| {
+                        let count = counts.get(&(x, y)).copied().unwrap_or(0);
+                        ((x, y), self.rule.next_counted(self.get(x, y), count))
+                    })
+                    .filter(|(_, c)| !c.is_dead())
+                    .collect()
+            }
+            None => alive
+                .flat_map(|(x, y)| {
+                    hensel::offsets()
+                        .iter()
+                        .map(move |&(dx, dy)| (x + dx as i64, y + dy as i64))
+                })
+                .chain(self.cells.keys().copied())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .map(|(x, y)| {
+                    let neighborhood = hensel::offsets()
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (dx, dy))| self.is_alive(x + *dx as i64, y + *dy as i64))
+                        .fold(0, |acc, (bit, _)| acc | 1 << bit);
+                    ((x, y), self.rule.next(self.get(x, y), neighborhood))
+                })
+                .filter(|(_, c)| !c.is_dead())
+                .collect(),
+        };
+        self.bounds = Self::bounds_of(next.keys());
+        self.cells = next;
+    }
+
+    fn bounds_of<'a>(positions: impl Iterator<Item = &'a (i64, i64)>) -> Option<BoundingBox> {
+        positions.fold(None, |bounds, &(x, y)| match bounds {
+            Some(mut bounds) => {
+                bounds.include(x, y);
+                Some(bounds)
+            }
+            None => Some(BoundingBox::new(x, y)),
+        })
+    }
+}
+
+impl Display for Plane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.bounds {
+            Some(bounds) => self.rule.geometry().render(
+                f,
+                bounds.width() as usize,
+                bounds.height() as usize,
+                |x, y| self.get(bounds.min_x + x as i64, bounds.min_y + y as i64),
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plane::{BoundingBox, Plane},
+        rule::RuleError,
+        Cell,
+    };
+
+    fn glider() -> Plane {
+        let mut plane = Plane::new();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            plane.set(x, y, Cell::alive());
+        }
+        plane
+    }
+
+    #[test]
+    fn glider_travels_test() {
+        let mut plane = glider();
+        for _ in 0..400 {
+            plane.update();
+        }
+        assert_eq!(plane.population(), 5);
+        assert_eq!(
+            plane.bounding_box(),
+            Some(BoundingBox {
+                min_x: 100,
+                min_y: 100,
+                max_x: 102,
+                max_y: 102
+            })
+        );
+        assert!(plane.is_alive(101, 100));
+        assert!(!plane.is_alive(1, 0));
+    }
+
+    #[test]
+    fn set_updates_bounding_box_test() {
+        let mut plane = glider();
+        plane.set(-5, 7, Cell::alive());
+        assert_eq!(plane.bounding_box().unwrap().min_x, -5);
+        plane.set(-5, 7, Cell::dead());
+        assert_eq!(plane.bounding_box().unwrap().min_x, 0);
+        assert_eq!(plane.to_string(), " # \n  #\n###");
+    }
+
+    #[test]
+    fn birth_from_nothing_test() {
+        assert_eq!(
+            Plane::new().with_rule("B0/S8".parse().unwrap()).err(),
+            Some(RuleError::Unbounded)
+        );
+        assert!(Plane::new()
+            .with_rule("R2,C0,M1,S2..4,B3..3,NN".parse().unwrap())
+            .is_ok());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, Sender},
+    thread::{self, JoinHandle},
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+type Task<'a> = Box<dyn FnOnce() + Send + 'a>;
+
+/// Threads that live as long as the pool and run tasks handed to them, so stepping a field
+/// on several threads doesn't spawn new ones every generation.
+pub(crate) struct Pool {
+    jobs: Vec<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Pool {
+    pub(crate) fn new(threads: usize) -> Self {
+        let (jobs, workers) = (0..threads.max(1))
+            .map(|_| {
+                let (sender, receiver) = mpsc::channel::<Job>();
+                let worker = thread::spawn(move || receiver.into_iter().for_each(|job| job()));
+                (sender, worker)
+            })
+            .unzip();
+        Self { jobs, workers }
+    }
+
+    /// Runs task i on worker `i % threads` and returns once every task has finished, so the
+    /// tasks may borrow from the caller like in `std::thread::scope`.
+    ///
+    /// # Panics
+    ///
+    /// If a task panicked.
+    pub(crate) fn run<'a>(&self, tasks: impl IntoIterator<Item = Task<'a>>) {
+        // Taken out of the iterator before any is sent, so an iterator that panics can't
+        // leave jobs running after the borrows they hold have ended.
+        let tasks: Vec<_> = tasks.into_iter().collect();
+        let (done, finished) = mpsc::channel();
+        let mut running = 0;
+        for (i, task) in tasks.into_iter().enumerate() {
+            let done = done.clone();
+            let job: Task<'a> = Box::new(move || {
+                let ok = panic::catch_unwind(AssertUnwindSafe(task)).is_ok();
+                let _ = done.send(ok);
+            });
+            // SAFETY: the job only borrows for `'a`, and it has either run or been dropped
+            // by the time this function returns: nothing between here and the wait below
+            // can panic, workers never stop while the pool is alive, panics inside tasks
+            // are caught, and every job that was sent reports back.
+            let job = unsafe { std::mem::transmute::<Task<'a>, Job>(job) };
+            if self.jobs[i % self.jobs.len()].send(job).is_ok() {
+                running += 1;
+            }
+        }
+        drop(done);
+        let panicked = finished.iter().take(running).filter(|&ok| !ok).count();
+        assert!(panicked == 0, "{} pool tasks panicked", panicked);
+    }
+}
+
+impl Drop for Pool {
+    fn drop(&mut self) {
+        // Closing the channels ends the workers' loops.
+        self.jobs.clear();
+        self.workers.drain(..).for_each(|worker| {
+            let _ = worker.join();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        panic::{self, AssertUnwindSafe},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+        thread,
+    };
+
+    use crate::pool::Pool;
+
+    #[test]
+    fn reuses_threads_test() {
+        let pool = Pool::new(3);
+        let ids = || {
+            let ids = Mutex::new(HashSet::new());
+            pool.run((0..6).map(|_| -> Box<dyn FnOnce() + Send> {
+                Box::new(|| {
+                    ids.lock().unwrap().insert(thread::current().id());
+                })
+            }));
+            ids.into_inner().unwrap()
+        };
+        let first = ids();
+        assert_eq!(first.len(), 3);
+        assert_eq!(ids(), first);
+        assert!(!first.contains(&thread::current().id()));
+
+        let mut sums = [0; 4];
+        pool.run(
+            sums.iter_mut()
+                .enumerate()
+                .map(|(i, sum)| -> Box<dyn FnOnce() + Send> {
+                    Box::new(move || *sum = (0..=i).sum())
+                }),
+        );
+        assert_eq!(sums, [0, 1, 3, 6]);
+    }
+
+    #[test]
+    fn panicking_tasks_test() {
+        let pool = Pool::new(2);
+        let ran = AtomicUsize::new(0);
+        let task = || -> Box<dyn FnOnce() + Send + '_> {
+            Box::new(|| {
+                ran.fetch_add(1, Ordering::SeqCst);
+            })
+        };
+        // Nothing is sent when the iterator panics halfway.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.run((0..4).map(|i| {
+                assert!(i < 2, "iterator panicked");
+                task()
+            }))
+        }));
+        assert!(result.is_err());
+        assert_eq!(ran.load(Ordering::SeqCst), 0);
+
+        // A panic inside a task is reported once the others have finished.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.run([task(), Box::new(|| panic!("task panicked")), task()])
+        }));
+        assert!(result.is_err());
+        assert_eq!(ran.load(Ordering::SeqCst), 2);
+        pool.run([task()]);
+        assert_eq!(ran.load(Ordering::SeqCst), 3);
+    }
+}
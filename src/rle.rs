@@ -0,0 +1,385 @@
+//! Run-length encoded pattern files.
+
+use std::fmt::Display;
+
+use crate::{
+    pattern::Pattern,
+    rule::{Rule, RuleError},
+    Cell,
+};
+
+const LINE_LENGTH: usize = 70;
+
+/// Error reading an RLE file. Lines and columns count from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RleError {
+    /// No `x = ..., y = ...` line before the cells.
+    MissingHeader,
+    /// Header line that can't be read.
+    InvalidHeader(usize, usize, String),
+    /// The header holds an invalid rule.
+    InvalidRule(usize, RuleError),
+    /// `#` line of an unknown kind.
+    InvalidComment(usize, String),
+    /// Character that isn't a run count, a state or a row separator.
+    UnexpectedChar(usize, usize, char),
+    /// Cells beyond the size given in the header.
+    OutOfBounds(usize, usize),
+    /// State the rule doesn't have.
+    InvalidState(usize, usize, u8),
+}
+
+impl Display for RleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RleError::MissingHeader => write!(f, "missing 'x = ..., y = ...' header line"),
+            RleError::InvalidHeader(line, column, s) => write!(
+                f,
+                "line {}, column {}: invalid header entry '{}'",
+                line, column, s
+            ),
+            RleError::InvalidRule(line, e) => write!(f, "line {}: {}", line, e),
+            RleError::InvalidComment(line, s) => {
+                write!(f, "line {}: invalid comment line '{}'", line, s)
+            }
+            RleError::UnexpectedChar(line, column, c) => write!(
+                f,
+                "line {}, column {}: unexpected character '{}'",
+                line, column, c
+            ),
+            RleError::OutOfBounds(line, column) => write!(
+                f,
+                "line {}, column {}: cells extend past the size given in the header",
+                line, column
+            ),
+            RleError::InvalidState(line, column, state) => write!(
+                f,
+                "line {}, column {}: state {} is not valid for the rule",
+                line, column, state
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RleError {}
+
+// `b`/`o` for two states, `.` and `A` to `X`, `pA` to `pX`, ..., `yA` to `yO` for up to
+// 256 states.
+fn tag(cell: Cell, multistate: bool) -> String {
+    match (cell.state(), multistate) {
+        (0, false) => "b".to_string(),
+        (_, false) => "o".to_string(),
+        (0, true) => ".".to_string(),
+        (state, true) => {
+            let (prefix, letter) = ((state - 1) / 24, (state - 1) % 24);
+            let letter = (b'A' + letter) as char;
+            match prefix {
+                0 => letter.to_string(),
+                prefix => format!("{}{}", (b'o' + prefix) as char, letter),
+            }
+        }
+    }
+}
+
+/// Reads an RLE file, with its `#N`, `#O` and `#C` lines and the rule from the header.
+pub fn parse(s: &str) -> Result<Pattern, RleError> {
+    let mut pattern = Pattern::default();
+    let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    // Comments, then the header.
+    let (header_line, header) = loop {
+        let (number, line) = lines.next().ok_or(RleError::MissingHeader)?;
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix('#') else {
+            break (number, line);
+        };
+        let mut chars = comment.chars();
+        let kind = chars.next();
+        let text = chars.as_str().trim().to_string();
+        match kind {
+            Some('N') => pattern.name = Some(text),
+            Some('O') => pattern.author = Some(text),
+            Some('C' | 'c') => pattern.comments.push(text),
+            Some('P' | 'R') => {
+                let coordinates: Vec<i64> = text
+                    .split_whitespace()
+                    .map(|n| n.parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| RleError::InvalidComment(number, line.to_string()))?;
+                let [x, y] = coordinates[..] else {
+                    return Err(RleError::InvalidComment(number, line.to_string()));
+                };
+                pattern.origin = (x, y);
+            }
+            Some('r') => {
+                let rule = text.parse().map_err(|e| RleError::InvalidRule(number, e))?;
+                pattern.rule = Some(rule);
+            }
+            // Golly treats any other comment line as a plain comment.
+            _ => pattern.comments.push(line[1..].trim().to_string()),
+        }
+    };
+    parse_header(header_line, header, &mut pattern)?;
+
+    let states = pattern.rule.map_or(u8::MAX, |rule: Rule| rule.states() - 1);
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut count: Option<usize> = None;
+    let mut prefix: Option<u8> = None;
+    'body: for (number, line) in lines {
+        for (column, c) in line.char_indices().map(|(i, c)| (i + 1, c)) {
+            let unexpected = RleError::UnexpectedChar(number, column, c);
+            let out_of_bounds = || RleError::OutOfBounds(number, column);
+            let state = match c {
+                _ if prefix.is_some() && !c.is_ascii_uppercase() => return Err(unexpected),
+                '0'..='9' => {
+                    let digit = c as usize - '0' as usize;
+                    count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                    continue;
+                }
+                '!' => break 'body,
+                c if c.is_whitespace() => continue,
+                '$' => {
+                    y = y
+                        .checked_add(count.take().unwrap_or(1))
+                        .ok_or_else(out_of_bounds)?;
+                    x = 0;
+                    continue;
+                }
+                'p'..='y' => {
+                    prefix = Some(c as u8 - b'o');
+                    continue;
+                }
+                'b' | '.' => 0,
+                'o' => 1,
+                'A'..='X' => {
+                    let letter = c as u8 - b'A' + 1;
+                    let state = u16::from(prefix.take().unwrap_or(0)) * 24 + u16::from(letter);
+                    u8::try_from(state).map_err(|_| unexpected)?
+                }
+                _ => return Err(unexpected),
+            };
+            if state > states {
+                return Err(RleError::InvalidState(number, column, state));
+            }
+            let end = x
+                .checked_add(count.take().unwrap_or(1))
+                .ok_or_else(out_of_bounds)?;
+            if state != 0 {
+                if end > pattern.width || y >= pattern.height {
+                    return Err(out_of_bounds());
+                }
+                pattern
+                    .cells
+                    .extend((x..end).map(|x| ((x, y), Cell::dying(state))));
+            }
+            x = end;
+        }
+    }
+    Ok(pattern)
+}
+
+// `x = 3, y = 2, rule = B3/S23`. The rule comes last as Larger than Life rules contain
+// commas themselves.
+fn parse_header(number: usize, line: &str, pattern: &mut Pattern) -> Result<(), RleError> {
+    let mut rest = line;
+    let (mut width, mut height) = (None, None);
+    while !rest.trim().is_empty() {
+        let column = line.len() - rest.len() + 1;
+        let invalid = |s: &str| RleError::InvalidHeader(number, column, s.trim().to_string());
+        let (key, value) = rest.split_once('=').ok_or_else(|| invalid(rest))?;
+        let key = key.trim().trim_start_matches(',').trim();
+        if key == "rule" {
+            let rule = value
+                .trim()
+                .parse()
+                .map_err(|e| RleError::InvalidRule(number, e))?;
+            pattern.rule = Some(rule);
+            break;
+        }
+        let (value, tail) = value.split_once(',').unwrap_or((value, ""));
+        let size = value.trim().parse().map_err(|_| invalid(rest))?;
+        match key {
+            "x" => width = Some(size),
+            "y" => height = Some(size),
+            _ => return Err(invalid(key)),
+        }
+        rest = tail;
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => {
+            pattern.width = width;
+            pattern.height = height;
+            Ok(())
+        }
+        _ => Err(RleError::MissingHeader),
+    }
+}
+
+/// Writes the pattern as RLE, with lines of at most 70 characters.
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    let mut comment = |kind: char, text: &str| out.push_str(&format!("#{} {}\n", kind, text));
+    if let Some(name) = &pattern.name {
+        comment('N', name);
+    }
+    if let Some(author) = &pattern.author {
+        comment('O', author);
+    }
+    pattern.comments.iter().for_each(|text| comment('C', text));
+    if pattern.origin != (0, 0) {
+        comment('R', &format!("{} {}", pattern.origin.0, pattern.origin.1));
+    }
+    out.push_str(&format!("x = {}, y = {}", pattern.width, pattern.height));
+    if let Some(rule) = &pattern.rule {
+        out.push_str(&format!(", rule = {}", rule));
+    }
+    out.push('\n');
+
+    let multistate = pattern.rule.is_some_and(|rule| rule.states() > 2)
+        || pattern.cells.iter().any(|(_, c)| c.state() > 1);
+    let run = |count: usize, tag: &str| match count {
+        1 => tag.to_string(),
+        count => format!("{}{}", count, tag),
+    };
+    let mut tokens = Vec::new();
+    let mut newlines = 0;
+    for row in pattern.rows() {
+        let end = row.iter().rposition(|c| !c.is_dead()).map_or(0, |i| i + 1);
+        if end > 0 && newlines > 0 {
+            tokens.push(run(newlines, "$"));
+            newlines = 0;
+        }
+        let mut x = 0;
+        while x < end {
+            let length = row[x..end].iter().take_while(|&&c| c == row[x]).count();
+            tokens.push(run(length, &tag(row[x], multistate)));
+            x += length;
+        }
+        newlines += 1;
+    }
+    tokens.push("!".to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if line.len() + token.len() > LINE_LENGTH {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pattern::Pattern,
+        rle::{parse, write, RleError},
+        rule::{Rule, RuleError},
+        Cell,
+    };
+
+    const GOSPER: &str = "#N Gosper glider gun
+#O Bill Gosper
+#C A true period 30 glider gun.
+#C The first known gun and the first known finite pattern with unbounded growth.
+x = 36, y = 9, rule = B3/S23
+24bo11b$22bobo11b$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o14b$2o8b
+o3bob2o4bobo11b$10bo5bo7bo11b$11bo3bo20b$12b2o!
+";
+
+    #[test]
+    fn parse_test() {
+        let gun = parse(GOSPER).unwrap();
+        assert_eq!(gun.name.as_deref(), Some("Gosper glider gun"));
+        assert_eq!(gun.author.as_deref(), Some("Bill Gosper"));
+        assert_eq!(gun.comments.len(), 2);
+        assert_eq!(gun.rule, Some(Rule::conway()));
+        assert_eq!((gun.width, gun.height), (36, 9));
+        assert_eq!(gun.cells.len(), 36);
+        assert!(gun.cells.contains(&((24, 0), Cell::alive())));
+        assert!(gun.cells.contains(&((13, 8), Cell::alive())));
+    }
+
+    #[test]
+    fn write_test() {
+        let gun = parse(GOSPER).unwrap();
+        let rle = write(&gun);
+        assert!(rle
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .all(|line| line.len() <= 70));
+        assert!(rle.contains("x = 36, y = 9, rule = B3/S23\n24bo$22bobo$12b2o6b2o12b2o$"));
+        assert_eq!(parse(&rle), Ok(gun));
+
+        let glider = parse("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        assert_eq!(write(&glider), "x = 3, y = 3\nbo$2bo$3o!\n");
+        let gaps = parse("#R -5 7\nx = 2, y = 5\no3$bo!").unwrap();
+        assert_eq!(gaps.origin, (-5, 7));
+        assert_eq!(write(&gaps), "#R -5 7\nx = 2, y = 5\no3$bo!\n");
+    }
+
+    #[test]
+    fn multistate_test() {
+        let rle = "x = 5, y = 2, rule = B2/S345/C4\n.AB$2C.pA!";
+        assert_eq!(parse(rle), Err(RleError::InvalidState(2, 9, 25)),);
+        let rle = "x = 5, y = 2, rule = R2,C30,M0,S1..2,B3..3,NN\n.AB$2CX!";
+        let pattern = parse(rle).unwrap();
+        assert_eq!(
+            pattern.cells,
+            vec![
+                ((1, 0), Cell::alive()),
+                ((2, 0), Cell::dying(2)),
+                ((0, 1), Cell::dying(3)),
+                ((1, 1), Cell::dying(3)),
+                ((2, 1), Cell::dying(24)),
+            ]
+        );
+        assert_eq!(write(&pattern).lines().last(), Some(".AB$2CX!"));
+
+        let wide = Pattern {
+            width: 2,
+            height: 1,
+            cells: vec![((0, 0), Cell::dying(25)), ((1, 0), Cell::dying(255))],
+            ..Pattern::default()
+        };
+        assert_eq!(write(&wide), "x = 2, y = 1\npAyO!\n");
+        assert_eq!(parse(&write(&wide)), Ok(wide));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!(parse("#C only\n"), Err(RleError::MissingHeader));
+        assert_eq!(
+            parse("x = 3, z = 2\n3o!"),
+            Err(RleError::InvalidHeader(1, 7, "z".to_string()))
+        );
+        assert_eq!(
+            parse("x = 3, y = 1, rule = B9/S\n3o!"),
+            Err(RleError::InvalidRule(1, RuleError::NeighborCount(9)))
+        );
+        assert_eq!(
+            parse("#P 1\nx = 3, y = 1\n3o!"),
+            Err(RleError::InvalidComment(1, "#P 1".to_string()))
+        );
+        assert_eq!(
+            parse("x = 3, y = 2\n3o$\nbko!"),
+            Err(RleError::UnexpectedChar(3, 2, 'k'))
+        );
+        assert_eq!(parse("x = 3, y = 1\n4o!"), Err(RleError::OutOfBounds(2, 2)));
+        assert_eq!(
+            parse("x = 3, y = 3\no99999999999999999999999o!"),
+            Err(RleError::OutOfBounds(2, 25))
+        );
+        assert_eq!(
+            parse("x = 3, y = 3\n99999999999999999999999$$o!"),
+            Err(RleError::OutOfBounds(2, 25))
+        );
+    }
+}
@@ -0,0 +1,673 @@
+//! Birth and survival rules and their rulestrings.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    geometry::Geometry,
+    hensel,
+    ltl::Ltl,
+    neighborhood::{Neighborhood, Shape},
+    Cell,
+};
+
+const MAX_NEIGHBORS: usize = 8;
+
+/// Names of well known rules with their rulestrings, see [`Rule::preset`].
+pub const PRESETS: &[(&str, &str)] = &[
+    ("Life", "B3/S23"),
+    ("HighLife", "B36/S23"),
+    ("Day & Night", "B3678/S34678"),
+    ("Seeds", "B2/S"),
+    ("Life without Death", "B3/S012345678"),
+    ("Diamoeba", "B35678/S5678"),
+    ("2x2", "B36/S125"),
+    ("Morley", "B368/S245"),
+    ("Anneal", "B4678/S35678"),
+    ("Replicator", "B1357/S1357"),
+    ("Maze", "B3/S12345"),
+    ("Coral", "B3/S45678"),
+    ("Brian's Brain", "B2/S/C3"),
+    ("Star Wars", "B2/S345/C4"),
+    ("Frogs", "B34/S12/C3"),
+    ("Bloomerang", "B34678/S234/C24"),
+    ("Hexagonal Life", "B2/S34H"),
+    ("Triangular Life", "B45/S34T"),
+    ("Bosco's Rule", "R5,C0,M1,S34..58,B34..45,NM"),
+    ("Majority", "R4,C0,M1,S41..81,B41..81,NM"),
+    ("Waffle", "R7,C0,M1,S100..200,B75..170,NM"),
+    ("Globe", "R8,C0,M0,S163..223,B74..252,NM"),
+];
+
+/// Error reading a rulestring.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RuleError {
+    /// The rulestring is empty.
+    Empty,
+    /// Character that doesn't belong at its place.
+    UnexpectedChar(char),
+    /// Neighbor count beyond the size of the neighborhood.
+    NeighborCount(usize),
+    /// Section given twice.
+    DuplicateSection(char),
+    /// More `/`-separated sections than the notation has.
+    TooManySections,
+    /// Number of states below two or not a number.
+    InvalidStates(String),
+    /// Hensel letter that doesn't exist for the neighbor count.
+    InvalidLetter(usize, char),
+    /// Neighbor count with `-` but no letters after it.
+    MissingLetters(usize),
+    /// Section the notation needs but isn't there.
+    MissingSection(char),
+    /// Invalid value of a Larger than Life section.
+    InvalidValue(char, String),
+    /// Rule with B0, which an unbounded universe can't run.
+    Unbounded,
+    /// Rule the named engine can't run.
+    Unsupported(&'static str),
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleError::Empty => write!(f, "rule string is empty"),
+            RuleError::UnexpectedChar(c) => write!(f, "unexpected character '{}' in rule", c),
+            RuleError::NeighborCount(n) => write!(
+                f,
+                "neighbor count {} is out of range for the neighborhood",
+                n
+            ),
+            RuleError::DuplicateSection(c) => write!(f, "section '{}' is specified twice", c),
+            RuleError::TooManySections => write!(f, "too many '/'-separated sections"),
+            RuleError::InvalidStates(s) => write!(f, "invalid number of states '{}'", s),
+            RuleError::InvalidLetter(n, c) => {
+                write!(f, "letter '{}' is not valid for neighbor count {}", c, n)
+            }
+            RuleError::Unbounded => write!(
+                f,
+                "rules where cells are born without live neighbors need a bounded grid"
+            ),
+            RuleError::Unsupported(engine) => {
+                write!(f, "the {} engine only runs two-state Moore rules", engine)
+            }
+            RuleError::MissingSection(c) => write!(f, "section '{}' is missing", c),
+            RuleError::InvalidValue(c, s) => {
+                write!(f, "invalid value '{}' for section '{}'", s, c)
+            }
+            RuleError::MissingLetters(n) => {
+                write!(
+                    f,
+                    "'-' after neighbor count {} must be followed by letters",
+                    n
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// Set of Moore neighborhood configurations, indexed as described in `hensel`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Transitions([u64; 4]);
+
+impl Transitions {
+    fn totalistic(counts: impl IntoIterator<Item = usize>) -> Result<Self, RuleError> {
+        counts
+            .into_iter()
+            .try_fold(Self::default(), |mut transitions, n| {
+                transitions.insert_count(n)?;
+                Ok(transitions)
+            })
+    }
+
+    fn contains(&self, configuration: u8) -> bool {
+        self.0[configuration as usize / 64] & (1 << (configuration % 64)) != 0
+    }
+
+    fn insert(&mut self, configuration: u8) {
+        self.0[configuration as usize / 64] |= 1 << (configuration % 64);
+    }
+
+    fn remove(&mut self, configuration: u8) {
+        self.0[configuration as usize / 64] &= !(1 << (configuration % 64));
+    }
+
+    fn insert_count(&mut self, n: usize) -> Result<(), RuleError> {
+        if n > MAX_NEIGHBORS {
+            return Err(RuleError::NeighborCount(n));
+        }
+        (0..=u8::MAX)
+            .filter(|c| c.count_ones() as usize == n)
+            .for_each(|c| self.insert(c));
+        Ok(())
+    }
+
+    fn contains_letter(&self, n: usize, letter: char) -> bool {
+        hensel::configurations(n, letter)
+            .into_iter()
+            .flatten()
+            .all(|c| self.contains(c))
+    }
+
+    fn parse(s: &str) -> Result<Self, RuleError> {
+        let mut transitions = Self::default();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            let n = c.to_digit(10).ok_or(RuleError::UnexpectedChar(c))? as usize;
+            let negated = chars.next_if_eq(&'-').is_some();
+            let mut letters = Vec::new();
+            while let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+                letters.push(letter.to_ascii_lowercase());
+            }
+            if letters.is_empty() {
+                if negated {
+                    return Err(RuleError::MissingLetters(n));
+                }
+                transitions.insert_count(n)?;
+                continue;
+            }
+            if n > MAX_NEIGHBORS {
+                return Err(RuleError::NeighborCount(n));
+            }
+            if negated {
+                transitions.insert_count(n)?;
+            }
+            for letter in letters {
+                let configurations =
+                    hensel::configurations(n, letter).ok_or(RuleError::InvalidLetter(n, letter))?;
+                for c in configurations {
+                    if negated {
+                        transitions.remove(c);
+                    } else {
+                        transitions.insert(c);
+                    }
+                }
+            }
+        }
+        Ok(transitions)
+    }
+}
+
+impl Display for Transitions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for n in 0..=MAX_NEIGHBORS {
+            let (present, absent): (Vec<char>, Vec<char>) =
+                hensel::letters(n).partition(|&letter| self.contains_letter(n, letter));
+            let any = (0..=u8::MAX)
+                .filter(|c| c.count_ones() as usize == n)
+                .any(|c| self.contains(c));
+            if !any {
+                continue;
+            }
+            write!(f, "{}", n)?;
+            if absent.is_empty() {
+                continue;
+            }
+            if present.len() <= absent.len() {
+                present.iter().try_for_each(|c| write!(f, "{}", c))?;
+            } else {
+                write!(f, "-")?;
+                absent.iter().try_for_each(|c| write!(f, "{}", c))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Isotropic {
+        birth: Transitions,
+        survival: Transitions,
+    },
+    // Outer-totalistic rules on neighborhoods other than the square Moore one.
+    Totalistic {
+        neighborhood: Neighborhood,
+        birth: u32,
+        survival: u32,
+    },
+    LargerThanLife(Ltl),
+}
+
+/// Rule of a Life-like cellular automaton: which cells are born and which survive, and how
+/// many states dying cells go through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    kind: Kind,
+    states: u8,
+}
+
+impl Rule {
+    /// Outer totalistic two-state rule on the Moore neighborhood, e.g.
+    /// `Rule::new(&[3], &[2, 3])` for B3/S23.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Result<Self, RuleError> {
+        Ok(Self {
+            kind: Kind::Isotropic {
+                birth: Transitions::totalistic(birth.iter().copied())?,
+                survival: Transitions::totalistic(survival.iter().copied())?,
+            },
+            states: 2,
+        })
+    }
+
+    /// Conway's Game of Life, B3/S23.
+    pub fn conway() -> Self {
+        Self::new(&[3], &[2, 3]).unwrap()
+    }
+
+    /// Same rule with the given number of states, as in Generations rules. Live cells that
+    /// don't survive go through the states above 1 before they die.
+    pub fn with_states(mut self, states: u8) -> Result<Self, RuleError> {
+        if states < 2 {
+            return Err(RuleError::InvalidStates(states.to_string()));
+        }
+        self.states = states;
+        Ok(self)
+    }
+
+    /// Number of states, 2 unless cells take generations to die.
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// Rule of a preset by its name, ignoring case.
+    pub fn preset(name: &str) -> Option<Self> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .and_then(|(_, rule)| rule.parse().ok())
+    }
+
+    /// Rules that only look at the number of live cells in a neighborhood rather than at
+    /// the exact Moore configuration.
+    pub fn neighborhood(&self) -> Option<Neighborhood> {
+        match &self.kind {
+            Kind::Isotropic { .. } => None,
+            Kind::Totalistic { neighborhood, .. } => Some(*neighborhood),
+            Kind::LargerThanLife(ltl) => Some(ltl.neighborhood()),
+        }
+    }
+
+    /// Grid the rule is meant for.
+    pub fn geometry(&self) -> Geometry {
+        self.neighborhood()
+            .map_or(Geometry::Square, |n| n.shape().geometry())
+    }
+
+    /// Next state of a cell whose Moore neighbors are alive where the bits of
+    /// `configuration` are set, in the order of [`hensel::offsets`].
+    pub fn next(&self, cell: Cell, configuration: u8) -> Cell {
+        match &self.kind {
+            Kind::Isotropic { birth, survival } => self.decay(
+                cell,
+                birth.contains(configuration),
+                survival.contains(configuration),
+            ),
+            _ => self.next_counted(cell, configuration.count_ones() as usize),
+        }
+    }
+
+    /// Next state of a cell with `count` live neighbors.
+    pub fn next_counted(&self, cell: Cell, count: usize) -> Cell {
+        match &self.kind {
+            Kind::Isotropic { birth, survival } => {
+                let born =
+                    (0..=u8::MAX).any(|c| c.count_ones() as usize == count && birth.contains(c));
+                let survives =
+                    (0..=u8::MAX).any(|c| c.count_ones() as usize == count && survival.contains(c));
+                self.decay(cell, born, survives)
+            }
+            Kind::Totalistic {
+                birth, survival, ..
+            } => self.decay(
+                cell,
+                birth & (1 << count) != 0,
+                survival & (1 << count) != 0,
+            ),
+            Kind::LargerThanLife(ltl) => self.decay(cell, ltl.born(count), ltl.survives(count)),
+        }
+    }
+
+    fn decay(&self, cell: Cell, born: bool, survives: bool) -> Cell {
+        match cell.state() {
+            0 if born => Cell::alive(),
+            0 => Cell::dead(),
+            1 if survives => Cell::alive(),
+            state if state + 1 < self.states => Cell::dying(state + 1),
+            _ => Cell::dead(),
+        }
+    }
+
+    fn parse_states(s: &str) -> Result<u8, RuleError> {
+        s.parse::<u8>()
+            .ok()
+            .filter(|&states| states >= 2)
+            .ok_or_else(|| RuleError::InvalidStates(s.to_string()))
+    }
+
+    fn parse_counts(s: &str, size: usize) -> Result<u32, RuleError> {
+        let mut mask = 0;
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            let n = c.to_digit(10).ok_or(RuleError::UnexpectedChar(c))? as usize;
+            if let Some(letter) = chars.next_if(char::is_ascii_alphabetic) {
+                return Err(RuleError::InvalidLetter(n, letter));
+            }
+            if n > size {
+                return Err(RuleError::NeighborCount(n));
+            }
+            mask |= 1 << n;
+        }
+        Ok(mask)
+    }
+
+    fn from_sections(
+        birth: &str,
+        survival: &str,
+        states: u8,
+        shape: Option<Shape>,
+    ) -> Result<Self, RuleError> {
+        let kind = match shape {
+            None => Kind::Isotropic {
+                birth: Transitions::parse(birth)?,
+                survival: Transitions::parse(survival)?,
+            },
+            Some(shape) => {
+                let neighborhood = Neighborhood::new(shape, 1, false);
+                Kind::Totalistic {
+                    neighborhood,
+                    birth: Self::parse_counts(birth, neighborhood.size())?,
+                    survival: Self::parse_counts(survival, neighborhood.size())?,
+                }
+            }
+        };
+        Ok(Self { kind, states })
+    }
+
+    // Splits `B3/S23/C3`-style rules into their birth, survival and state count sections.
+    fn parse_prefixed(s: &str) -> Result<(&str, &str, u8), RuleError> {
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+        let mut rest = s;
+        while let Some(prefix) = rest.chars().next() {
+            let consumed = &s[..s.len() - rest.len()];
+            let (name, body) = match prefix.to_ascii_uppercase() {
+                name @ ('B' | 'S' | 'C' | 'G') => (name, &rest[1..]),
+                '/' if !consumed.is_empty() => {
+                    rest = &rest[1..];
+                    continue;
+                }
+                // A bare number after the birth and survival sections is a state count.
+                '0'..='9' if consumed.ends_with('/') => ('C', rest),
+                _ => return Err(RuleError::UnexpectedChar(prefix)),
+            };
+            let end = match name {
+                'B' | 'S' => body.find(|c: char| c == '/' || "BSGbsg".contains(c)),
+                _ => body.find(|c: char| !c.is_ascii_digit()),
+            }
+            .unwrap_or(body.len());
+            let (section, tail) = body.split_at(end);
+            let duplicate = match name {
+                'B' => birth.replace(section).is_some(),
+                'S' => survival.replace(section).is_some(),
+                _ => states.replace(Self::parse_states(section)?).is_some(),
+            };
+            if duplicate {
+                return Err(RuleError::DuplicateSection(name));
+            }
+            rest = tail;
+        }
+        Ok((
+            birth.unwrap_or_default(),
+            survival.unwrap_or_default(),
+            states.unwrap_or(2),
+        ))
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(RuleError::Empty);
+        }
+        if let Some(rule) = Self::preset(s) {
+            return Ok(rule);
+        }
+        if Ltl::is_ltl(s) {
+            let (ltl, states) = Ltl::parse(s)?;
+            return Ok(Self {
+                kind: Kind::LargerThanLife(ltl),
+                states,
+            });
+        }
+
+        // Golly's suffixes for non-Moore neighborhoods. `T` has to be upper case since `t`
+        // is also a Hensel letter.
+        let (s, shape) = match s.chars().last() {
+            Some('H' | 'h') => (&s[..s.len() - 1], Some(Shape::Hexagonal)),
+            Some('T') => (&s[..s.len() - 1], Some(Shape::Triangular)),
+            Some('V' | 'v') => (&s[..s.len() - 1], Some(Shape::VonNeumann)),
+            _ => (s, None),
+        };
+        if s.starts_with(|c: char| c.is_ascii_digit() || c == '/') {
+            let mut sections = s.split('/');
+            let survival = sections.next().unwrap_or_default();
+            let birth = sections.next().unwrap_or_default();
+            let states = sections.next().map_or(Ok(2), Self::parse_states)?;
+            if sections.next().is_some() {
+                return Err(RuleError::TooManySections);
+            }
+            Self::from_sections(birth, survival, states, shape)
+        } else {
+            let (birth, survival, states) = Self::parse_prefixed(s)?;
+            Self::from_sections(birth, survival, states, shape)
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            Kind::Isotropic { birth, survival } => {
+                write!(f, "B{}/S{}", birth, survival)?;
+                if self.states > 2 {
+                    write!(f, "/C{}", self.states)?;
+                }
+                Ok(())
+            }
+            Kind::Totalistic {
+                neighborhood,
+                birth,
+                survival,
+            } => {
+                let digits = |mask: u32| -> String {
+                    (0..=neighborhood.size())
+                        .filter(|n| mask & (1 << n) != 0)
+                        .map(|n| n.to_string())
+                        .collect()
+                };
+                write!(f, "B{}/S{}", digits(*birth), digits(*survival))?;
+                if self.states > 2 {
+                    write!(f, "/C{}", self.states)?;
+                }
+                write!(f, "{}", neighborhood.shape())
+            }
+            Kind::LargerThanLife(ltl) => {
+                let states = if self.states > 2 { self.states } else { 0 };
+                write!(f, "R{},C{},{}", ltl.neighborhood().radius(), states, ltl)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        geometry::Geometry,
+        rule::{Rule, RuleError},
+        Cell,
+    };
+
+    #[test]
+    fn parse_test() {
+        let highlife = Rule::new(&[3, 6], &[2, 3]).unwrap();
+        assert_eq!("B36/S23".parse(), Ok(highlife));
+        assert_eq!("b36s23".parse(), Ok(highlife));
+        assert_eq!("S23/B36".parse(), Ok(highlife));
+        assert_eq!("23/36".parse(), Ok(highlife));
+        assert_eq!("HighLife".parse(), Ok(highlife));
+        assert_eq!("B2/S".parse(), Ok(Rule::new(&[2], &[]).unwrap()));
+        assert_eq!(Rule::conway().to_string(), "B3/S23");
+    }
+
+    #[test]
+    fn parse_generations_test() {
+        let star_wars = Rule::new(&[2], &[3, 4, 5]).unwrap().with_states(4).unwrap();
+        assert_eq!("B2/S345/C4".parse(), Ok(star_wars));
+        assert_eq!("B2/S345/4".parse(), Ok(star_wars));
+        assert_eq!("345/2/4".parse(), Ok(star_wars));
+        assert_eq!("g4b2s345".parse(), Ok(star_wars));
+        assert_eq!("Star Wars".parse(), Ok(star_wars));
+        assert_eq!(star_wars.to_string(), "B2/S345/C4");
+        assert_eq!(
+            "B2/S/C1".parse::<Rule>(),
+            Err(RuleError::InvalidStates("1".to_string()))
+        );
+        assert_eq!(
+            "B2/S/C".parse::<Rule>(),
+            Err(RuleError::InvalidStates("".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_isotropic_test() {
+        let rule: Rule = "B2-a/S12".parse().unwrap();
+        assert_eq!(rule.next(Cell::dead(), 0b0000_0101), Cell::alive());
+        assert_eq!(rule.next(Cell::dead(), 0b0000_0011), Cell::dead());
+        assert_eq!(rule.next(Cell::alive(), 0b0000_0001), Cell::alive());
+        assert_eq!(rule.to_string(), "B2-a/S12");
+
+        let tlife: Rule = "B3/S2-i34q".parse().unwrap();
+        assert_eq!(tlife.next(Cell::alive(), 0b0100_0010), Cell::dead());
+        assert_eq!(tlife.next(Cell::alive(), 0b0000_0011), Cell::alive());
+        assert_eq!(tlife.to_string(), "B3/S2-i34q");
+        assert_eq!("B2ce3/S".parse::<Rule>().unwrap().to_string(), "B2ce3/S");
+        assert_eq!("b2-a3s12".parse(), "B2-a3/S12".parse::<Rule>());
+    }
+
+    #[test]
+    fn parse_isotropic_error_test() {
+        assert_eq!(
+            "B1k/S".parse::<Rule>(),
+            Err(RuleError::InvalidLetter(1, 'k'))
+        );
+        assert_eq!(
+            "B0c/S".parse::<Rule>(),
+            Err(RuleError::InvalidLetter(0, 'c'))
+        );
+        assert_eq!("B2-/S".parse::<Rule>(), Err(RuleError::MissingLetters(2)));
+        assert_eq!("B-a/S".parse::<Rule>(), Err(RuleError::UnexpectedChar('-')));
+    }
+
+    #[test]
+    fn parse_geometry_test() {
+        let hex: Rule = "B2/S34H".parse().unwrap();
+        assert_eq!(hex.geometry(), Geometry::Hexagonal);
+        assert_eq!(hex.to_string(), "B2/S34H");
+        assert_eq!("34/2h".parse(), Ok(hex));
+        assert_eq!(hex.next_counted(Cell::dead(), 2), Cell::alive());
+        assert_eq!(hex.next_counted(Cell::alive(), 2), Cell::dead());
+
+        let tri: Rule = "B45/S34/C3T".parse().unwrap();
+        assert_eq!(tri.geometry(), Geometry::Triangular);
+        assert_eq!(tri.neighborhood().unwrap().size(), 12);
+        assert_eq!(tri.to_string(), "B45/S34/C3T");
+        assert_eq!(
+            "B3/S2V".parse::<Rule>().unwrap().geometry(),
+            Geometry::Square
+        );
+
+        assert_eq!("B7/S34H".parse::<Rule>(), Err(RuleError::NeighborCount(7)));
+        assert_eq!(
+            "B2a/S34H".parse::<Rule>(),
+            Err(RuleError::InvalidLetter(2, 'a'))
+        );
+    }
+
+    #[test]
+    fn parse_ltl_test() {
+        let bosco: Rule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+        assert_eq!(bosco, Rule::preset("Bosco's Rule").unwrap());
+        assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!(bosco.neighborhood().unwrap().size(), 121);
+        assert_eq!(bosco.next_counted(Cell::dead(), 34), Cell::alive());
+        assert_eq!(bosco.next_counted(Cell::alive(), 59), Cell::dead());
+
+        let rule: Rule = "R2,C3,M0,S1..2,B3..3,NN".parse().unwrap();
+        assert_eq!(rule.states(), 3);
+        assert_eq!(rule.to_string(), "R2,C3,M0,S1..2,B3..3,NN");
+        assert_eq!(
+            "R1,C0,M0,S2..3,B3..3".parse::<Rule>().unwrap().to_string(),
+            "R1,C0,M0,S2..3,B3..3,NM"
+        );
+    }
+
+    #[test]
+    fn parse_ltl_error_test() {
+        assert_eq!(
+            "R0,C0,M0,S2..3,B3..3,NM".parse::<Rule>(),
+            Err(RuleError::InvalidValue('R', "0".to_string()))
+        );
+        assert_eq!(
+            "R1,C0,M0,S3..2,B3..3,NM".parse::<Rule>(),
+            Err(RuleError::InvalidValue('S', "3..2".to_string()))
+        );
+        assert_eq!(
+            "R1,C0,M0,S2..3,NM".parse::<Rule>(),
+            Err(RuleError::MissingSection('B'))
+        );
+        assert_eq!(
+            "R1,C0,M0,S2..9,B3..3,NM".parse::<Rule>(),
+            Err(RuleError::NeighborCount(9))
+        );
+        assert_eq!(
+            "R1,C0,M0,S2..3,B3..3,NX".parse::<Rule>(),
+            Err(RuleError::InvalidValue('N', "X".to_string()))
+        );
+        assert_eq!("R1,é".parse::<Rule>(), Err(RuleError::UnexpectedChar('é')));
+    }
+
+    #[test]
+    fn generations_next_test() {
+        let brain = Rule::preset("Brian's Brain").unwrap();
+        assert_eq!(brain.next(Cell::dead(), 0b0000_0011), Cell::alive());
+        assert_eq!(brain.next(Cell::alive(), 0b0000_0011), Cell::dying(2));
+        assert_eq!(brain.next(Cell::dying(2), 0b0000_0011), Cell::dead());
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert_eq!("".parse::<Rule>(), Err(RuleError::Empty));
+        assert_eq!("B39/S23".parse::<Rule>(), Err(RuleError::NeighborCount(9)));
+        assert_eq!(
+            "B3/X23".parse::<Rule>(),
+            Err(RuleError::UnexpectedChar('X'))
+        );
+        assert_eq!(
+            "B3/B23".parse::<Rule>(),
+            Err(RuleError::DuplicateSection('B'))
+        );
+        assert_eq!("23/3/4/1".parse::<Rule>(), Err(RuleError::TooManySections));
+    }
+}
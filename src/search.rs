@@ -0,0 +1,329 @@
+//! Searching random soups for rare objects, in the spirit of apgsearch.
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+use crate::{
+    apgcode::Kind,
+    census::{plane_objects, Census, Object},
+    history::mix,
+    pattern::Pattern,
+    plane::Plane,
+    rule::{Rule, RuleError},
+    soup::{Soup, Symmetry},
+    topology::WrapMode,
+    Cell, GameOfLife,
+};
+
+// Longest period a settled soup may have, which is also the longest period objects are
+// named up to.
+const HISTORY: usize = 256;
+// Generations between two checks of whether a soup has settled.
+const CHECK_INTERVAL: u64 = 64;
+const GLIDER: &str = "xq4_153";
+
+/// Seed of the soup named by a seed string. Searches name their soups by a root string
+/// followed by the number of the soup, so any soup can be run again from its name.
+pub fn seed_from_str(s: &str) -> u64 {
+    // FNV-1a, finished off with a mix so that similar names give unrelated seeds.
+    let hash = s.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    mix(hash)
+}
+
+/// Whether an object is worth a closer look: anything that didn't settle, oscillators
+/// beyond period 2 and spaceships other than the glider.
+pub fn is_rare(object: &Object) -> bool {
+    match &object.apgcode {
+        None => true,
+        Some(code) => match code.kind {
+            Kind::StillLife => false,
+            Kind::Oscillator(period) => period > 2,
+            Kind::Spaceship(_) => code.to_string() != GLIDER,
+        },
+    }
+}
+
+/// Batch of random soups run on the unbounded plane until they settle, in the spirit of
+/// apgsearch, so that the census can be compared with Catagolue's.
+#[derive(Debug, Clone)]
+pub struct Search {
+    root: String,
+    soups: u64,
+    width: usize,
+    height: usize,
+    rule: Rule,
+    density: f64,
+    symmetry: Symmetry,
+    generations: u64,
+    threads: usize,
+}
+
+impl Search {
+    /// Search of the 16x16 soups `root0` to `root{soups - 1}`, using every core.
+    pub fn new(root: impl Into<String>, soups: u64) -> Self {
+        Self {
+            root: root.into(),
+            soups,
+            width: 16,
+            height: 16,
+            rule: Rule::default(),
+            density: 0.5,
+            symmetry: Symmetry::C1,
+            generations: 10_000,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Size of the area each soup fills before it runs on the plane.
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Rule the soups run, B3/S23 by default. Rules with B0 can't run on the plane.
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Probability for a soup cell to be alive, 0.5 by default.
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Symmetry of the soups, C1 by default.
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Generations a soup may take to settle before it is censused as it is.
+    pub fn with_generations(mut self, generations: u64) -> Self {
+        self.generations = generations;
+        self
+    }
+
+    /// Threads running soups, one per core by default.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Seed string of the n-th soup.
+    pub fn seed_string(&self, soup: u64) -> String {
+        format!("{}{}", self.root, soup)
+    }
+
+    /// Runs every soup, spread over the threads. The report doesn't depend on the number
+    /// of threads.
+    pub fn run(&self) -> Result<Report, RuleError> {
+        // Fails before any thread starts for rules the plane can't run.
+        Plane::new().with_rule(self.rule)?;
+        let next = AtomicU64::new(0);
+        let mut report = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut report = Report::default();
+                        loop {
+                            let soup = next.fetch_add(1, Ordering::Relaxed);
+                            if soup >= self.soups {
+                                break Ok(report);
+                            }
+                            self.run_soup(soup, &mut report)?;
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .try_fold(Report::default(), |mut total, report| {
+                    let report = report?;
+                    total.soups += report.soups;
+                    total.unsettled += report.unsettled;
+                    total.census += &report.census;
+                    total.finds.extend(report.finds);
+                    Ok::<_, RuleError>(total)
+                })
+        })?;
+        report.finds.sort_by_key(|find| find.soup);
+        Ok(report)
+    }
+
+    fn run_soup(&self, soup: u64, report: &mut Report) -> Result<(), RuleError> {
+        let seed = self.seed_string(soup);
+        let recipe = Soup::new(seed_from_str(&seed))
+            .with_density(self.density)
+            .with_symmetry(self.symmetry);
+        let start = GameOfLife::from_soup(self.width, self.height, WrapMode::NoWrap, &recipe)
+            .with_rule(self.rule);
+        let mut plane = Plane::new().with_rule(self.rule)?;
+        for (x, y) in start.live_cells() {
+            plane.set(x as i64, y as i64, Cell::alive());
+        }
+
+        let mut populations = VecDeque::with_capacity(2 * HISTORY);
+        let mut settled = false;
+        for generation in 1..=self.generations {
+            plane.update();
+            if populations.len() == 2 * HISTORY {
+                populations.pop_front();
+            }
+            populations.push_back(plane.population());
+            if generation % CHECK_INTERVAL == 0 && is_settled(&populations) {
+                settled = true;
+                break;
+            }
+        }
+
+        report.soups += 1;
+        if !settled {
+            report.unsettled += 1;
+        }
+        let mut rare = Vec::new();
+        for object in plane_objects(&plane, HISTORY as u64)? {
+            let code = object.code();
+            if is_rare(&object) {
+                rare.push(code.clone());
+            }
+            report.census.add(code, 1);
+        }
+        if !rare.is_empty() {
+            let pattern = Pattern {
+                name: Some(seed.clone()),
+                comments: rare.clone(),
+                ..Pattern::from_game(&start)
+            };
+            report.finds.push(Find {
+                soup,
+                seed,
+                codes: rare,
+                pattern,
+            });
+        }
+        Ok(())
+    }
+}
+
+// Whether the populations of the last `2 * HISTORY` generations repeat with a period of at
+// most `HISTORY`, which is how apgsearch tells that a soup has settled: still lifes,
+// oscillators and gliders flying off all keep the population periodic.
+fn is_settled(populations: &VecDeque<usize>) -> bool {
+    populations.len() == 2 * HISTORY
+        && (1..=HISTORY).any(|period| {
+            (period..populations.len()).all(|i| populations[i] == populations[i - period])
+        })
+}
+
+/// Soup that left rare objects behind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Find {
+    /// Number of the soup within the search.
+    pub soup: u64,
+    /// Seed string of the soup.
+    pub seed: String,
+    /// Codes of the rare objects, `zz_UNKNOWN` for those that didn't settle.
+    pub codes: Vec<String>,
+    /// The soup as it started, named after its seed string with the codes as comments.
+    pub pattern: Pattern,
+}
+
+/// Outcome of a search.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Number of soups run.
+    pub soups: u64,
+    /// Soups still changing when they ran out of generations.
+    pub unsettled: u64,
+    /// Objects left behind by all soups.
+    pub census: Census,
+    /// Soups with rare objects, by number.
+    pub finds: Vec<Find>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} soups, {} objects, {} soups didn't settle",
+            self.soups,
+            self.census.total(),
+            self.unsettled
+        )?;
+        writeln!(f)?;
+        write!(f, "{}", self.census)?;
+        if !self.finds.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Rare objects:")?;
+            for find in &self.finds {
+                writeln!(f, "  {}: {}", find.seed, find.codes.join(" "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::{
+        census::Object,
+        search::{is_rare, is_settled, seed_from_str, Search, HISTORY},
+    };
+
+    #[test]
+    fn seed_test() {
+        assert_eq!(seed_from_str("abc0"), seed_from_str("abc0"));
+        assert_ne!(seed_from_str("abc0"), seed_from_str("abc1"));
+        assert_ne!(seed_from_str(""), 0);
+
+        let object = |code: &str| Object {
+            cells: Vec::new(),
+            apgcode: code.parse().ok(),
+        };
+        assert!(!is_rare(&object("xs4_33")));
+        assert!(!is_rare(&object("xp2_7")));
+        assert!(!is_rare(&object("xq4_153")));
+        assert!(is_rare(&object("xp15_4r4z4r4")));
+        assert!(is_rare(&object("xq4_27dee6")));
+        assert!(is_rare(&object("zz_UNKNOWN")));
+    }
+
+    #[test]
+    fn settled_test() {
+        // Populations of a soup that settled into oscillators of period 2.
+        let periodic: VecDeque<_> = (0..2 * HISTORY).map(|i| 8 + i % 2).collect();
+        assert!(is_settled(&periodic));
+        let growing: VecDeque<_> = (0..2 * HISTORY).map(|i| 8 + i / 100).collect();
+        assert!(!is_settled(&growing));
+        assert!(!is_settled(&periodic.iter().copied().skip(1).collect()));
+    }
+
+    #[test]
+    fn search_test() {
+        let search = Search::new("test_", 6).with_generations(1000);
+        let report = search.clone().with_threads(4).run().unwrap();
+        assert_eq!(report, search.with_threads(1).run().unwrap());
+        assert_eq!(report.soups, 6);
+        assert!(report.unsettled < 6);
+        assert!(report.census.count("xs4_33") > 0);
+        for find in &report.finds {
+            assert_eq!(find.seed, format!("test_{}", find.soup));
+            assert_eq!(find.pattern.name.as_ref(), Some(&find.seed));
+        }
+        assert!(report.to_string().starts_with("6 soups, "));
+
+        let b0 = Search::new("", 1).with_rule("B03/S23".parse().unwrap());
+        assert!(b0.run().is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! Reproducible random soups with apgsearch's symmetries.
+
+use std::{fmt::Display, str::FromStr};
+
+use rand_chacha::{
+    rand_core::{RngCore, SeedableRng},
+    ChaCha8Rng,
+};
+
+/// Rectangle of a field, in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area {
+    /// Column of the left edge.
+    pub x: usize,
+    /// Row of the top edge.
+    pub y: usize,
+    /// Number of columns.
+    pub width: usize,
+    /// Number of rows.
+    pub height: usize,
+}
+
+const IDENTITY: [i64; 4] = [1, 0, 0, 1];
+const ROTATE_90: [i64; 4] = [0, -1, 1, 0];
+const ROTATE_180: [i64; 4] = [-1, 0, 0, -1];
+const ROTATE_270: [i64; 4] = [0, 1, -1, 0];
+const FLIP_X: [i64; 4] = [-1, 0, 0, 1];
+const FLIP_Y: [i64; 4] = [1, 0, 0, -1];
+const FLIP_DIAGONAL: [i64; 4] = [0, 1, 1, 0];
+const FLIP_ANTIDIAGONAL: [i64; 4] = [0, -1, -1, 0];
+
+/// Symmetries of apgsearch's soups. The number after the group tells where its center
+/// lies: 1 in the middle of a cell, 2 in the middle of an edge and 4 on a corner.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    /// No symmetry.
+    #[default]
+    C1,
+    /// `C2_1`, half turn.
+    C2Cell,
+    /// `C2_2`
+    C2Edge,
+    /// `C2_4`
+    C2Corner,
+    /// `C4_1`, quarter turn.
+    C4Cell,
+    /// `C4_4`
+    C4Corner,
+    /// `D2_+1`, mirrored across a horizontal axis.
+    D2Cell,
+    /// `D2_+2`
+    D2Edge,
+    /// `D2_x`, mirrored across a diagonal.
+    D2Diagonal,
+    /// `D4_+1`, mirrored across both axes.
+    D4Cell,
+    /// `D4_+2`
+    D4Edge,
+    /// `D4_+4`
+    D4Corner,
+    /// `D4_x1`, mirrored across both diagonals.
+    D4DiagonalCell,
+    /// `D4_x4`
+    D4DiagonalCorner,
+    /// `D8_1`, every rotation and reflection of the square.
+    D8Cell,
+    /// `D8_4`
+    D8Corner,
+}
+
+impl Symmetry {
+    /// Every symmetry.
+    pub const ALL: [Symmetry; 16] = [
+        Symmetry::C1,
+        Symmetry::C2Cell,
+        Symmetry::C2Edge,
+        Symmetry::C2Corner,
+        Symmetry::C4Cell,
+        Symmetry::C4Corner,
+        Symmetry::D2Cell,
+        Symmetry::D2Edge,
+        Symmetry::D2Diagonal,
+        Symmetry::D4Cell,
+        Symmetry::D4Edge,
+        Symmetry::D4Corner,
+        Symmetry::D4DiagonalCell,
+        Symmetry::D4DiagonalCorner,
+        Symmetry::D8Cell,
+        Symmetry::D8Corner,
+    ];
+
+    // Matrices [a, b, c, d] mapping (x, y) to (ax + by, cx + dy) around the center.
+    fn transforms(&self) -> &'static [[i64; 4]] {
+        match self {
+            Symmetry::C1 => &[IDENTITY],
+            Symmetry::C2Cell | Symmetry::C2Edge | Symmetry::C2Corner => &[IDENTITY, ROTATE_180],
+            Symmetry::C4Cell | Symmetry::C4Corner => &[IDENTITY, ROTATE_90, ROTATE_180, ROTATE_270],
+            Symmetry::D2Cell | Symmetry::D2Edge => &[IDENTITY, FLIP_Y],
+            Symmetry::D2Diagonal => &[IDENTITY, FLIP_DIAGONAL],
+            Symmetry::D4Cell | Symmetry::D4Edge | Symmetry::D4Corner => {
+                &[IDENTITY, FLIP_X, FLIP_Y, ROTATE_180]
+            }
+            Symmetry::D4DiagonalCell | Symmetry::D4DiagonalCorner => {
+                &[IDENTITY, FLIP_DIAGONAL, FLIP_ANTIDIAGONAL, ROTATE_180]
+            }
+            Symmetry::D8Cell | Symmetry::D8Corner => &[
+                IDENTITY,
+                ROTATE_90,
+                ROTATE_180,
+                ROTATE_270,
+                FLIP_X,
+                FLIP_Y,
+                FLIP_DIAGONAL,
+                FLIP_ANTIDIAGONAL,
+            ],
+        }
+    }
+
+    // Whether the center lies in the middle of a cell horizontally and vertically.
+    fn centered(&self) -> (bool, bool) {
+        match self {
+            Symmetry::C2Edge | Symmetry::D4Edge => (true, false),
+            Symmetry::C2Corner
+            | Symmetry::C4Corner
+            | Symmetry::D2Edge
+            | Symmetry::D4Corner
+            | Symmetry::D4DiagonalCorner
+            | Symmetry::D8Corner => (false, false),
+            _ => (true, true),
+        }
+    }
+
+    // Whether the seed only covers half of the field horizontally and vertically, the rest
+    // being filled by its images.
+    fn halved(&self) -> (bool, bool) {
+        match self {
+            Symmetry::C1 | Symmetry::D2Diagonal => (false, false),
+            Symmetry::C2Cell
+            | Symmetry::C2Edge
+            | Symmetry::C2Corner
+            | Symmetry::D2Cell
+            | Symmetry::D2Edge
+            | Symmetry::D4DiagonalCell
+            | Symmetry::D4DiagonalCorner => (false, true),
+            _ => (true, true),
+        }
+    }
+}
+
+/// Error reading a symmetry.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SoupError {
+    /// Name that isn't one of apgsearch's symmetries.
+    UnknownSymmetry(String),
+}
+
+impl Display for SoupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SoupError::UnknownSymmetry(s) => write!(f, "unknown symmetry '{}'", s),
+        }
+    }
+}
+
+impl std::error::Error for SoupError {}
+
+impl FromStr for Symmetry {
+    type Err = SoupError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Symmetry::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| SoupError::UnknownSymmetry(s.to_string()))
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Symmetry::C1 => "C1",
+            Symmetry::C2Cell => "C2_1",
+            Symmetry::C2Edge => "C2_2",
+            Symmetry::C2Corner => "C2_4",
+            Symmetry::C4Cell => "C4_1",
+            Symmetry::C4Corner => "C4_4",
+            Symmetry::D2Cell => "D2_+1",
+            Symmetry::D2Edge => "D2_+2",
+            Symmetry::D2Diagonal => "D2_x",
+            Symmetry::D4Cell => "D4_+1",
+            Symmetry::D4Edge => "D4_+2",
+            Symmetry::D4Corner => "D4_+4",
+            Symmetry::D4DiagonalCell => "D4_x1",
+            Symmetry::D4DiagonalCorner => "D4_x4",
+            Symmetry::D8Cell => "D8_1",
+            Symmetry::D8Corner => "D8_4",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Recipe for a random field. The same seed always gives the same soup: cells are drawn
+/// from ChaCha8 row by row, each alive when a 32-bit draw is below `density * 2^32`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soup {
+    seed: u64,
+    density: f64,
+    area: Option<Area>,
+    symmetry: Symmetry,
+}
+
+impl Soup {
+    /// Soup filling the whole field with half of the cells alive.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            density: 0.5,
+            area: None,
+            symmetry: Symmetry::C1,
+        }
+    }
+
+    /// Probability for a cell to be alive, clamped to `0.0..=1.0`.
+    pub fn with_density(mut self, density: f64) -> Self {
+        self.density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Only fills the given rectangle, leaving the rest of the field dead.
+    pub fn with_area(mut self, area: Area) -> Self {
+        self.area = Some(area);
+        self
+    }
+
+    /// Fills the area with a random seed and its images under the symmetry. Without an
+    /// area, the seed is the top left part of the field whose images fill the rest of it.
+    pub fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Symmetry of the soup.
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    /// Seed of the random generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Probability for a cell to be alive.
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+
+    /// Rectangle the soup fills, the whole field if `None`.
+    pub fn area(&self) -> Option<Area> {
+        self.area
+    }
+
+    /// Live cells of the soup on a `width * height` field, row by row.
+    pub fn cells(&self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        let (centered_x, centered_y) = self.symmetry.centered();
+        let (halved_x, halved_y) = self.symmetry.halved();
+        let seed_size = |size: usize, halved: bool, centered: bool| {
+            if halved {
+                (size + usize::from(centered)) / 2
+            } else {
+                size
+            }
+        };
+        let area = self.area.unwrap_or(Area {
+            x: 0,
+            y: 0,
+            width: seed_size(width, halved_x, centered_x),
+            height: seed_size(height, halved_y, centered_y),
+        });
+
+        // Coordinates are doubled so the center can lie between cells. It is placed right
+        // after a halved seed, on its last cell if the center is in the middle of one, and
+        // in the middle of the seed otherwise.
+        let center = |start: usize, size: usize, halved: bool, centered: bool| {
+            let (start, size, centered) = (start as i64, size as i64, i64::from(centered));
+            if halved {
+                2 * (start + size) - centered
+            } else {
+                2 * start + size - (size + centered) % 2
+            }
+        };
+        let center_x = center(area.x, area.width, halved_x, centered_x);
+        let center_y = center(area.y, area.height, halved_y, centered_y);
+        let threshold = (self.density * (1u64 << 32) as f64) as u64;
+        let images = |x: usize, y: usize| {
+            let (dx, dy) = (2 * x as i64 + 1 - center_x, 2 * y as i64 + 1 - center_y);
+            self.symmetry.transforms().iter().map(move |[a, b, c, d]| {
+                let x = (center_x + a * dx + b * dy - 1) / 2;
+                let y = (center_y + c * dx + d * dy - 1) / 2;
+                (x, y)
+            })
+        };
+        let in_area = |&(x, y): &(i64, i64)| {
+            (area.x as i64..(area.x + area.width) as i64).contains(&x)
+                && (area.y as i64..(area.y + area.height) as i64).contains(&y)
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut cells: Vec<(usize, usize)> = (area.y..area.y + area.height)
+            .flat_map(|y| (area.x..area.x + area.width).map(move |x| (x, y)))
+            // Only the first cell of every orbit in the area is drawn, like apgsearch seeds
+            // a fundamental domain, so images overlapping the area don't raise the density.
+            .filter(|&(x, y)| {
+                let first = images(x, y).filter(in_area).min_by_key(|&(x, y)| (y, x));
+                first == Some((x as i64, y as i64))
+            })
+            .filter(|_| u64::from(rng.next_u32()) < threshold)
+            .flat_map(|(x, y)| images(x, y))
+            .filter(|&(x, y)| (0..width as i64).contains(&x) && (0..height as i64).contains(&y))
+            .map(|(x, y)| (x as usize, y as usize))
+            .collect();
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::soup::{Area, Soup, SoupError, Symmetry};
+
+    #[test]
+    fn reproducible_test() {
+        let soup = Soup::new(42).with_density(0.3);
+        assert_eq!(soup.cells(64, 64), soup.cells(64, 64));
+        assert_ne!(
+            soup.cells(64, 64),
+            Soup::new(43).with_density(0.3).cells(64, 64)
+        );
+        // Pinned so a change of generator or sampling shows up as a failure.
+        assert_eq!(soup.cells(64, 64).len(), 1242);
+        assert_eq!(soup.cells(64, 64)[..3], [(0, 0), (2, 0), (9, 0)]);
+    }
+
+    #[test]
+    fn density_test() {
+        assert!(Soup::new(1).with_density(0.0).cells(20, 20).is_empty());
+        assert_eq!(Soup::new(1).with_density(1.5).cells(20, 20).len(), 400);
+        let half = Soup::new(1).cells(100, 100).len();
+        assert!((4500..5500).contains(&half));
+    }
+
+    #[test]
+    fn area_test() {
+        let area = Area {
+            x: 5,
+            y: 8,
+            width: 10,
+            height: 4,
+        };
+        let cells = Soup::new(7).with_density(1.0).with_area(area).cells(12, 20);
+        assert_eq!(cells.len(), 7 * 4);
+        assert!(cells
+            .iter()
+            .all(|&(x, y)| (5..12).contains(&x) && (8..12).contains(&y)));
+    }
+
+    #[test]
+    fn symmetry_test() {
+        for symmetry in Symmetry::ALL {
+            let (centered_x, centered_y) = symmetry.centered();
+            let (width, height) = (32 - centered_x as i64, 32 - centered_y as i64);
+            let cells: HashSet<(i64, i64)> = Soup::new(3)
+                .with_symmetry(symmetry)
+                .cells(width as usize, height as usize)
+                .into_iter()
+                .map(|(x, y)| (x as i64, y as i64))
+                .collect();
+            assert!(cells.len() > 400, "{}", symmetry);
+            for [a, b, c, d] in symmetry.transforms() {
+                // Doubled coordinates relative to the center of the field.
+                let image: HashSet<_> = cells
+                    .iter()
+                    .map(|&(x, y)| (2 * x + 1 - width, 2 * y + 1 - height))
+                    .map(|(x, y)| (a * x + b * y, c * x + d * y))
+                    .map(|(x, y)| ((x + width - 1) / 2, (y + height - 1) / 2))
+                    .collect();
+                assert_eq!(image, cells, "{}", symmetry);
+            }
+            assert_eq!(symmetry.to_string().parse(), Ok(symmetry));
+
+            // The images of the seed don't overlap it, so the density is as asked for.
+            let live: usize = (0..32)
+                .map(|seed| {
+                    let soup = Soup::new(seed).with_symmetry(symmetry).with_density(0.3);
+                    soup.cells(width as usize, height as usize).len()
+                })
+                .sum();
+            let density = live as f64 / (32 * width * height) as f64;
+            assert!((0.27..0.33).contains(&density), "{} {}", symmetry, density);
+        }
+        assert_eq!("d2_+1".parse(), Ok(Symmetry::D2Cell));
+        assert_eq!(
+            "C3".parse::<Symmetry>(),
+            Err(SoupError::UnknownSymmetry("C3".to_string()))
+        );
+    }
+}
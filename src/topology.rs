@@ -0,0 +1,315 @@
+//! How the edges of a bounded field are joined.
+
+use std::{fmt::Display, str::FromStr};
+
+/// Pair of edges of the field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// The top and bottom edges.
+    Horizontal,
+    /// The left and right edges.
+    Vertical,
+}
+
+/// How the edges of the field are joined. `Axis::Horizontal` refers to the top and bottom
+/// edges, `Axis::Vertical` to the left and right ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Torus joining both pairs of opposite edges.
+    Wrap,
+    /// Plane without wrapping, cells beyond the edges are dead.
+    NoWrap,
+    /// Cylinder wrapping only across the given edges.
+    Cylinder(Axis),
+    /// Torus where crossing the given edges also moves `shift` cells along them.
+    ShiftedTorus(Axis, isize),
+    /// Torus where the given edges are joined with a reflection.
+    Klein(Axis),
+    /// Both pairs of opposite edges joined with a reflection.
+    CrossSurface,
+    /// Joins the top edge with the left one and the bottom edge with the right one, so the
+    /// field has to be square.
+    Sphere,
+}
+
+impl WrapMode {
+    /// Maps coordinates outside of a `width * height` field onto the cell they refer to.
+    pub fn wrap(&self, x: isize, y: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (w, h) = (width as isize, height as isize);
+        let reflect = |v: isize, size: isize, twisted: bool| {
+            if twisted {
+                size - 1 - v
+            } else {
+                v
+            }
+        };
+        let (x, y) = match *self {
+            WrapMode::Wrap => (x.rem_euclid(w), y.rem_euclid(h)),
+            WrapMode::NoWrap => (x, y),
+            WrapMode::Cylinder(Axis::Horizontal) => (x, y.rem_euclid(h)),
+            WrapMode::Cylinder(Axis::Vertical) => (x.rem_euclid(w), y),
+            WrapMode::ShiftedTorus(Axis::Horizontal, shift) => {
+                let x = x + y.div_euclid(h) * shift;
+                (x.rem_euclid(w), y.rem_euclid(h))
+            }
+            WrapMode::ShiftedTorus(Axis::Vertical, shift) => {
+                let y = y + x.div_euclid(w) * shift;
+                (x.rem_euclid(w), y.rem_euclid(h))
+            }
+            WrapMode::Klein(Axis::Horizontal) => {
+                let twisted = y.div_euclid(h) % 2 != 0;
+                (reflect(x.rem_euclid(w), w, twisted), y.rem_euclid(h))
+            }
+            WrapMode::Klein(Axis::Vertical) => {
+                let twisted = x.div_euclid(w) % 2 != 0;
+                (x.rem_euclid(w), reflect(y.rem_euclid(h), h, twisted))
+            }
+            WrapMode::CrossSurface => {
+                let x_twisted = y.div_euclid(h) % 2 != 0;
+                let y_twisted = x.div_euclid(w) % 2 != 0;
+                (
+                    reflect(x.rem_euclid(w), w, x_twisted),
+                    reflect(y.rem_euclid(h), h, y_twisted),
+                )
+            }
+            WrapMode::Sphere => match (x < 0, x >= w, y < 0, y >= h) {
+                (false, false, true, false) => (-y - 1, x),
+                (true, false, false, false) => (y, -x - 1),
+                (false, false, false, true) => (w - 1 - (y - h), x),
+                (false, true, false, false) => (y, h - 1 - (x - w)),
+                _ => (x, y),
+            },
+        };
+        ((0..w).contains(&x) && (0..h).contains(&y)).then_some((x as usize, y as usize))
+    }
+}
+
+/// Error reading a Golly bounded grid setting.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TopologyError {
+    /// The setting is empty.
+    Empty,
+    /// Grid type other than `P`, `T`, `K`, `C` and `S`.
+    UnknownKind(char),
+    /// Width, height or shift that isn't a number.
+    InvalidDimension(String),
+    /// `*` or shift where the grid type doesn't allow one.
+    InvalidModifier(String),
+    /// Topology the engine doesn't support.
+    Unsupported(Topology),
+}
+
+impl Display for TopologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopologyError::Empty => write!(f, "bounded grid specification is empty"),
+            TopologyError::UnknownKind(c) => write!(f, "unknown bounded grid type '{}'", c),
+            TopologyError::InvalidDimension(s) => write!(f, "invalid grid dimension '{}'", s),
+            TopologyError::InvalidModifier(s) => {
+                write!(f, "shift or twist '{}' is not valid for this grid", s)
+            }
+            TopologyError::Unsupported(topology) => {
+                write!(
+                    f,
+                    "bounded grid {} is not supported by this engine",
+                    topology
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TopologyError {}
+
+/// Golly's bounded grid notation, e.g. `T30,20`, `T30+5,20`, `K30*,20`, `C30,20` or `S30`.
+/// As in Golly a zero dimension on a torus leaves that axis unwrapped, so `T30,0` is a
+/// cylinder whose height is up to the field it is applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Topology {
+    /// Number of columns.
+    pub width: usize,
+    /// Number of rows.
+    pub height: usize,
+    /// How the edges are joined.
+    pub wrap: WrapMode,
+}
+
+impl Topology {
+    fn parse_dimension(s: &str) -> Result<(usize, bool, isize), TopologyError> {
+        let invalid = || TopologyError::InvalidDimension(s.to_string());
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let size = s[..end].parse().map_err(|_| invalid())?;
+        let mut rest = &s[end..];
+        let twisted = rest.starts_with('*');
+        if twisted {
+            rest = &rest[1..];
+        }
+        let shift = match rest.chars().next() {
+            None => 0,
+            Some('+' | '-') => rest.parse().map_err(|_| invalid())?,
+            Some(_) => return Err(invalid()),
+        };
+        Ok((size, twisted, shift))
+    }
+}
+
+impl FromStr for Topology {
+    type Err = TopologyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let first = s.chars().next().ok_or(TopologyError::Empty)?;
+        let kind = first.to_ascii_uppercase();
+        if !"PTKCS".contains(kind) {
+            return Err(TopologyError::UnknownKind(first));
+        }
+        let rest = &s[first.len_utf8()..];
+        let modifier = || TopologyError::InvalidModifier(s.to_string());
+        let mut dimensions = rest.splitn(2, ',');
+        let (width, width_twisted, width_shift) =
+            Self::parse_dimension(dimensions.next().unwrap_or_default())?;
+        let (height, height_twisted, height_shift) = match dimensions.next() {
+            Some(height) => Self::parse_dimension(height)?,
+            None if kind == 'S' => (width, false, 0),
+            None => return Err(TopologyError::InvalidDimension(String::new())),
+        };
+        let twisted = (width_twisted, height_twisted);
+        let shifted = (width_shift != 0, height_shift != 0);
+        if (kind != 'K' && twisted != (false, false)) || (kind != 'T' && shifted != (false, false))
+        {
+            return Err(modifier());
+        }
+
+        let wrap = match kind {
+            'P' => WrapMode::NoWrap,
+            'T' => match (width, height, shifted) {
+                (_, 0, (false, false)) => WrapMode::Cylinder(Axis::Vertical),
+                (0, _, (false, false)) => WrapMode::Cylinder(Axis::Horizontal),
+                (_, _, (false, false)) => WrapMode::Wrap,
+                (_, _, (true, false)) => WrapMode::ShiftedTorus(Axis::Horizontal, width_shift),
+                (_, _, (false, true)) => WrapMode::ShiftedTorus(Axis::Vertical, height_shift),
+                _ => return Err(modifier()),
+            },
+            'K' => match twisted {
+                (true, false) => WrapMode::Klein(Axis::Horizontal),
+                (false, true) => WrapMode::Klein(Axis::Vertical),
+                _ => return Err(modifier()),
+            },
+            'C' => WrapMode::CrossSurface,
+            'S' if width == height => WrapMode::Sphere,
+            _ => return Err(TopologyError::InvalidDimension(rest.to_string())),
+        };
+        let cylinder = matches!(wrap, WrapMode::Cylinder(_));
+        if (width == 0 || height == 0) && !(cylinder && width != height) {
+            return Err(TopologyError::InvalidDimension("0".to_string()));
+        }
+        Ok(Self {
+            width,
+            height,
+            wrap,
+        })
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (w, h) = (self.width, self.height);
+        match self.wrap {
+            WrapMode::Wrap => write!(f, "T{},{}", w, h),
+            WrapMode::NoWrap => write!(f, "P{},{}", w, h),
+            WrapMode::Cylinder(Axis::Vertical) => write!(f, "T{},0", w),
+            WrapMode::Cylinder(Axis::Horizontal) => write!(f, "T0,{}", h),
+            WrapMode::ShiftedTorus(Axis::Horizontal, shift) => write!(f, "T{}{:+},{}", w, shift, h),
+            WrapMode::ShiftedTorus(Axis::Vertical, shift) => write!(f, "T{},{}{:+}", w, h, shift),
+            WrapMode::Klein(Axis::Horizontal) => write!(f, "K{}*,{}", w, h),
+            WrapMode::Klein(Axis::Vertical) => write!(f, "K{},{}*", w, h),
+            WrapMode::CrossSurface => write!(f, "C{},{}", w, h),
+            WrapMode::Sphere => write!(f, "S{}", w),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::topology::{Axis, Topology, TopologyError, WrapMode};
+
+    #[test]
+    fn parse_test() {
+        let parse = |s: &str| s.parse::<Topology>().map(|t| t.wrap);
+        assert_eq!(parse("T30,20"), Ok(WrapMode::Wrap));
+        assert_eq!(parse("P30,20"), Ok(WrapMode::NoWrap));
+        assert_eq!(parse("T30,0"), Ok(WrapMode::Cylinder(Axis::Vertical)));
+        assert_eq!(
+            parse("T30+5,20"),
+            Ok(WrapMode::ShiftedTorus(Axis::Horizontal, 5))
+        );
+        assert_eq!(
+            parse("T30,20-1"),
+            Ok(WrapMode::ShiftedTorus(Axis::Vertical, -1))
+        );
+        assert_eq!(parse("K30*,20"), Ok(WrapMode::Klein(Axis::Horizontal)));
+        assert_eq!(parse("k30,20*"), Ok(WrapMode::Klein(Axis::Vertical)));
+        assert_eq!(parse("C30,20"), Ok(WrapMode::CrossSurface));
+        assert_eq!(parse("S30"), Ok(WrapMode::Sphere));
+
+        for s in [
+            "T30,20", "T30+5,20", "T30,20-1", "K30*,20", "K30,20*", "C30,20", "S30",
+        ] {
+            assert_eq!(s.parse::<Topology>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let parse = |s: &str| s.parse::<Topology>();
+        assert_eq!(parse(""), Err(TopologyError::Empty));
+        assert_eq!(parse("X30,20"), Err(TopologyError::UnknownKind('X')));
+        assert_eq!(parse("é30"), Err(TopologyError::UnknownKind('é')));
+        assert_eq!(
+            parse("Tabc,20"),
+            Err(TopologyError::InvalidDimension("abc".to_string()))
+        );
+        assert_eq!(
+            parse("K30,20"),
+            Err(TopologyError::InvalidModifier("K30,20".to_string()))
+        );
+        assert_eq!(
+            parse("P30*,20"),
+            Err(TopologyError::InvalidModifier("P30*,20".to_string()))
+        );
+        assert_eq!(
+            parse("S30,20"),
+            Err(TopologyError::InvalidDimension("30,20".to_string()))
+        );
+    }
+
+    #[test]
+    fn wrap_test() {
+        let (w, h) = (5, 4);
+        assert_eq!(WrapMode::Wrap.wrap(-1, 4, w, h), Some((4, 0)));
+        assert_eq!(WrapMode::NoWrap.wrap(-1, 0, w, h), None);
+        assert_eq!(
+            WrapMode::Cylinder(Axis::Vertical).wrap(5, 1, w, h),
+            Some((0, 1))
+        );
+        assert_eq!(WrapMode::Cylinder(Axis::Vertical).wrap(0, 4, w, h), None);
+        assert_eq!(
+            WrapMode::ShiftedTorus(Axis::Horizontal, 2).wrap(1, 4, w, h),
+            Some((3, 0))
+        );
+        assert_eq!(
+            WrapMode::Klein(Axis::Horizontal).wrap(1, -1, w, h),
+            Some((3, 3))
+        );
+        assert_eq!(
+            WrapMode::Klein(Axis::Vertical).wrap(5, 0, w, h),
+            Some((0, 3))
+        );
+        assert_eq!(WrapMode::CrossSurface.wrap(5, 0, w, h), Some((0, 3)));
+        assert_eq!(WrapMode::CrossSurface.wrap(5, -1, w, h), Some((4, 0)));
+        assert_eq!(WrapMode::Sphere.wrap(2, -1, 4, 4), Some((0, 2)));
+        assert_eq!(WrapMode::Sphere.wrap(-1, 2, 4, 4), Some((2, 0)));
+        assert_eq!(WrapMode::Sphere.wrap(4, 1, 4, 4), Some((1, 3)));
+        assert_eq!(WrapMode::Sphere.wrap(-1, -1, 4, 4), None);
+    }
+}
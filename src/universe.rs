@@ -0,0 +1,364 @@
+//! Common interface over the stepping engines.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    bitpacked::BitPacked,
+    hashlife::HashLife,
+    plane::{BoundingBox, Plane},
+    rule::{Rule, RuleError},
+    topology::{Topology, TopologyError},
+    Cell, GameOfLife,
+};
+
+/// Common interface of the stepping engines. Bounded engines map coordinates outside of
+/// their field through their topology and ignore cells that don't map onto it.
+pub trait Universe {
+    /// Cell at the given coordinates.
+    fn get(&self, x: i64, y: i64) -> Cell;
+
+    /// Sets the state of a cell.
+    fn set(&mut self, x: i64, y: i64, cell: Cell);
+
+    /// Advances the universe by the given number of generations.
+    fn step(&mut self, generations: u64);
+
+    /// Number of live cells, not counting dying ones.
+    fn population(&self) -> u64;
+
+    /// Smallest box containing every cell that isn't dead.
+    fn bounding_box(&self) -> Option<BoundingBox>;
+
+    /// Coordinates of the live cells.
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (i64, i64)> + '_>;
+
+    /// Whether the cell at the coordinates is alive.
+    fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.get(x, y).is_alive()
+    }
+}
+
+/// Error building a universe.
+#[derive(Debug, PartialEq, Eq)]
+pub enum UniverseError {
+    /// Engine name that isn't known.
+    UnknownEngine(String),
+    /// Rule the engine can't run.
+    Rule(RuleError),
+    /// Topology the engine doesn't support.
+    Topology(TopologyError),
+}
+
+impl Display for UniverseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UniverseError::UnknownEngine(s) => write!(f, "unknown engine '{}'", s),
+            UniverseError::Rule(e) => write!(f, "{}", e),
+            UniverseError::Topology(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UniverseError {}
+
+impl From<RuleError> for UniverseError {
+    fn from(e: RuleError) -> Self {
+        UniverseError::Rule(e)
+    }
+}
+
+impl From<TopologyError> for UniverseError {
+    fn from(e: TopologyError) -> Self {
+        UniverseError::Topology(e)
+    }
+}
+
+/// Engines a [`Universe`] can be built on, see [`Engine::build`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// `GameOfLife`, one cell per byte on a bounded grid.
+    Naive,
+    /// `BitPacked`, 64 cells per word on a bounded grid.
+    BitPacked,
+    /// `Plane`, a hash set of cells on an unbounded plane.
+    Sparse,
+    /// `HashLife`, a memoized quadtree on an unbounded plane.
+    HashLife,
+}
+
+impl Engine {
+    /// Every engine.
+    pub const ALL: [Engine; 4] = [
+        Engine::Naive,
+        Engine::BitPacked,
+        Engine::Sparse,
+        Engine::HashLife,
+    ];
+
+    /// Whether the engine runs on a field with edges.
+    pub fn is_bounded(&self) -> bool {
+        matches!(self, Engine::Naive | Engine::BitPacked)
+    }
+
+    /// Builds an empty universe. Unbounded engines ignore the topology.
+    pub fn build(
+        &self,
+        topology: Topology,
+        rule: Rule,
+    ) -> Result<Box<dyn Universe>, UniverseError> {
+        let Topology {
+            width,
+            height,
+            wrap,
+        } = topology;
+        Ok(match self {
+            Engine::Naive => Box::new(GameOfLife::empty(width, height, wrap).with_rule(rule)),
+            Engine::BitPacked => Box::new(BitPacked::new(width, height, wrap)?.with_rule(rule)?),
+            Engine::Sparse => Box::new(Plane::new().with_rule(rule)?),
+            Engine::HashLife => Box::new(HashLife::new().with_rule(rule)?),
+        })
+    }
+}
+
+impl FromStr for Engine {
+    type Err = UniverseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Engine::ALL
+            .into_iter()
+            .find(|engine| engine.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| UniverseError::UnknownEngine(s.to_string()))
+    }
+}
+
+impl Display for Engine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Engine::Naive => "naive",
+            Engine::BitPacked => "bitpacked",
+            Engine::Sparse => "sparse",
+            Engine::HashLife => "hashlife",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn bounds_of(cells: impl Iterator<Item = (i64, i64)>) -> Option<BoundingBox> {
+    cells.fold(None, |bounds, (x, y)| match bounds {
+        Some(mut bounds) => {
+            bounds.include(x, y);
+            Some(bounds)
+        }
+        None => Some(BoundingBox::new(x, y)),
+    })
+}
+
+impl Universe for GameOfLife {
+    fn get(&self, x: i64, y: i64) -> Cell {
+        GameOfLife::get(self, x as isize, y as isize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        if let Some((x, y)) = self
+            .wrap
+            .wrap(x as isize, y as isize, self.width, self.height)
+        {
+            GameOfLife::set(self, x, y, cell);
+        }
+    }
+
+    fn step(&mut self, generations: u64) {
+        (0..generations).for_each(|_| self.update());
+    }
+
+    fn population(&self) -> u64 {
+        GameOfLife::population(self) as u64
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        bounds_of(
+            (0..self.field.len())
+                .filter(|&i| !self.field[i].is_dead())
+                .map(|i| ((i % self.width) as i64, (i / self.width) as i64)),
+        )
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (i64, i64)> + '_> {
+        Box::new(GameOfLife::live_cells(self).map(|(x, y)| (x as i64, y as i64)))
+    }
+}
+
+impl Universe for BitPacked {
+    fn get(&self, x: i64, y: i64) -> Cell {
+        BitPacked::get(self, x as isize, y as isize).unwrap_or_default()
+    }
+
+    fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        if let Some((x, y)) = self
+            .wrap()
+            .wrap(x as isize, y as isize, self.width(), self.height())
+        {
+            BitPacked::set(self, x, y, cell);
+        }
+    }
+
+    fn step(&mut self, generations: u64) {
+        (0..generations).for_each(|_| self.update());
+    }
+
+    fn population(&self) -> u64 {
+        BitPacked::population(self) as u64
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        bounds_of(self.live_cells())
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (i64, i64)> + '_> {
+        Box::new(
+            (0..self.height() as i64)
+                .flat_map(move |y| (0..self.width() as i64).map(move |x| (x, y)))
+                .filter(|&(x, y)| BitPacked::is_alive(self, x as isize, y as isize)),
+        )
+    }
+}
+
+impl Universe for Plane {
+    fn get(&self, x: i64, y: i64) -> Cell {
+        Plane::get(self, x, y)
+    }
+
+    fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        Plane::set(self, x, y, cell);
+    }
+
+    fn step(&mut self, generations: u64) {
+        (0..generations).for_each(|_| self.update());
+    }
+
+    fn population(&self) -> u64 {
+        Plane::population(self) as u64
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Plane::bounding_box(self)
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (i64, i64)> + '_> {
+        Box::new(Plane::live_cells(self))
+    }
+}
+
+impl Universe for HashLife {
+    fn get(&self, x: i64, y: i64) -> Cell {
+        HashLife::get(self, x, y)
+    }
+
+    fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        HashLife::set(self, x, y, cell);
+    }
+
+    fn step(&mut self, generations: u64) {
+        HashLife::step(self, generations);
+    }
+
+    fn population(&self) -> u64 {
+        HashLife::population(self)
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        HashLife::bounding_box(self)
+    }
+
+    fn live_cells(&self) -> Box<dyn Iterator<Item = (i64, i64)> + '_> {
+        Box::new(HashLife::live_cells(self).into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        topology::{Topology, WrapMode},
+        universe::{bounds_of, Engine, Universe, UniverseError},
+        Cell,
+    };
+
+    fn build(engine: Engine, rule: &str) -> Box<dyn Universe> {
+        let topology = Topology {
+            width: 128,
+            height: 128,
+            wrap: WrapMode::NoWrap,
+        };
+        engine.build(topology, rule.parse().unwrap()).unwrap()
+    }
+
+    fn snapshot(universe: &dyn Universe) -> (u64, Vec<(i64, i64)>) {
+        let mut cells: Vec<_> = universe.live_cells().collect();
+        cells.sort_unstable();
+        (universe.population(), cells)
+    }
+
+    // The soup is far enough from the edges of the bounded engines that nothing reaches them
+    // within the tested generations.
+    #[test]
+    fn engines_agree_test() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let soup: Vec<(i64, i64)> = (44..84)
+            .flat_map(|y| (44..84).map(move |x| (x, y)))
+            .filter(|_| rng.gen_bool(0.4))
+            .collect();
+        for rule in ["B3/S23", "B36/S23", "B2/S"] {
+            let mut universes: Vec<_> = Engine::ALL
+                .into_iter()
+                .map(|engine| build(engine, rule))
+                .collect();
+            for universe in universes.iter_mut() {
+                soup.iter()
+                    .for_each(|&(x, y)| universe.set(x, y, Cell::alive()));
+            }
+            for generations in [0, 1, 5, 24] {
+                let expected = snapshot(universes[0].as_ref());
+                for (engine, universe) in Engine::ALL.iter().zip(universes.iter_mut()) {
+                    assert_eq!(snapshot(universe.as_ref()), expected, "{} {}", engine, rule);
+                    let bounds = bounds_of(expected.1.iter().copied());
+                    assert_eq!(universe.bounding_box(), bounds, "{} {}", engine, rule);
+                    universe.step(generations);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_get_test() {
+        for engine in Engine::ALL {
+            let mut universe = build(engine, "B3/S23");
+            universe.set(3, 4, Cell::alive());
+            universe.set(5, 4, Cell::alive());
+            universe.set(5, 4, Cell::dead());
+            assert!(universe.is_alive(3, 4), "{}", engine);
+            assert!(!universe.is_alive(5, 4), "{}", engine);
+            assert_eq!(universe.population(), 1, "{}", engine);
+            assert_eq!(universe.live_cells().collect::<Vec<_>>(), vec![(3, 4)]);
+        }
+    }
+
+    #[test]
+    fn engine_parse_test() {
+        assert_eq!("HashLife".parse(), Ok(Engine::HashLife));
+        assert_eq!(
+            "quick".parse::<Engine>(),
+            Err(UniverseError::UnknownEngine("quick".to_string()))
+        );
+        let topology = "S20".parse().unwrap();
+        assert!(Engine::BitPacked
+            .build(topology, "B3/S23".parse().unwrap())
+            .is_err());
+        assert!(Engine::Naive
+            .build(topology, "B2/S/C3".parse().unwrap())
+            .is_ok());
+    }
+}